@@ -0,0 +1,566 @@
+//! Flag types for the attribute columns of metadata tables, as defined in ECMA-335 II.23.1.
+
+use crate::flags::flags;
+
+flags! {
+    /// The flags of a TypeDef row.
+    pub struct TypeAttributes: u32 {
+        const INTERFACE = 0x20;
+        const ABSTRACT = 0x80;
+        const SEALED = 0x100;
+        const SPECIAL_NAME = 0x400;
+        const RT_SPECIAL_NAME = 0x800;
+        const IMPORT = 0x1000;
+        const SERIALIZABLE = 0x2000;
+        const WINDOWS_RUNTIME = 0x4000;
+        const HAS_SECURITY = 0x40000;
+        const BEFORE_FIELD_INIT = 0x100000;
+        /// Only valid on ExportedType rows.
+        const IS_TYPE_FORWARDER = 0x200000;
+    }
+    masks {
+        const VISIBILITY_MASK = 0x7;
+        const LAYOUT_MASK = 0x18;
+        const STRING_FORMAT_MASK = 0x30000;
+        /// Non-standard string encodings, only meaningful with [`StringFormat::Custom`].
+        const CUSTOM_FORMAT_MASK = 0xC00000;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TypeVisibility {
+    NotPublic,
+    Public,
+    NestedPublic,
+    NestedPrivate,
+    NestedFamily,
+    NestedAssembly,
+    NestedFamilyAndAssembly,
+    NestedFamilyOrAssembly,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TypeLayout {
+    Auto,
+    Sequential,
+    Explicit,
+    /// The reserved value `0x18`.
+    Unknown,
+}
+
+/// How strings are marshalled for a type's P/Invoke methods.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StringFormat {
+    Ansi,
+    Unicode,
+    Auto,
+    Custom,
+}
+
+impl TypeAttributes {
+    pub const fn visibility(self) -> TypeVisibility {
+        match self.0 & Self::VISIBILITY_MASK.0 {
+            0 => TypeVisibility::NotPublic,
+            1 => TypeVisibility::Public,
+            2 => TypeVisibility::NestedPublic,
+            3 => TypeVisibility::NestedPrivate,
+            4 => TypeVisibility::NestedFamily,
+            5 => TypeVisibility::NestedAssembly,
+            6 => TypeVisibility::NestedFamilyAndAssembly,
+            _ => TypeVisibility::NestedFamilyOrAssembly,
+        }
+    }
+
+    pub const fn is_nested(self) -> bool {
+        self.0 & Self::VISIBILITY_MASK.0 >= 2
+    }
+
+    pub const fn layout(self) -> TypeLayout {
+        match self.0 & Self::LAYOUT_MASK.0 {
+            0 => TypeLayout::Auto,
+            0x8 => TypeLayout::Sequential,
+            0x10 => TypeLayout::Explicit,
+            _ => TypeLayout::Unknown,
+        }
+    }
+
+    pub const fn string_format(self) -> StringFormat {
+        match self.0 & Self::STRING_FORMAT_MASK.0 {
+            0 => StringFormat::Ansi,
+            0x10000 => StringFormat::Unicode,
+            0x20000 => StringFormat::Auto,
+            _ => StringFormat::Custom,
+        }
+    }
+}
+
+/// The accessibility of a method or field.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MemberAccess {
+    /// The member can't be referenced, only defined.
+    CompilerControlled,
+    Private,
+    FamilyAndAssembly,
+    Assembly,
+    Family,
+    FamilyOrAssembly,
+    Public,
+    /// The reserved value `7`.
+    Unknown,
+}
+
+impl MemberAccess {
+    const fn from_bits(bits: u16) -> Self {
+        match bits & 0x7 {
+            0 => Self::CompilerControlled,
+            1 => Self::Private,
+            2 => Self::FamilyAndAssembly,
+            3 => Self::Assembly,
+            4 => Self::Family,
+            5 => Self::FamilyOrAssembly,
+            6 => Self::Public,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+flags! {
+    /// The flags of a MethodDef row.
+    pub struct MethodAttributes: u16 {
+        const UNMANAGED_EXPORT = 0x8;
+        const STATIC = 0x10;
+        const FINAL = 0x20;
+        const VIRTUAL = 0x40;
+        const HIDE_BY_SIG = 0x80;
+        /// The method always gets a new vtable slot instead of reusing a base class slot.
+        const NEW_SLOT = 0x100;
+        /// The method can only be overridden if it's also accessible.
+        const STRICT = 0x200;
+        const ABSTRACT = 0x400;
+        const SPECIAL_NAME = 0x800;
+        const RT_SPECIAL_NAME = 0x1000;
+        const PINVOKE_IMPL = 0x2000;
+        const HAS_SECURITY = 0x4000;
+        const REQUIRE_SEC_OBJECT = 0x8000;
+    }
+    masks {
+        const MEMBER_ACCESS_MASK = 0x7;
+    }
+}
+
+impl MethodAttributes {
+    pub const fn access(self) -> MemberAccess {
+        MemberAccess::from_bits(self.0)
+    }
+}
+
+flags! {
+    /// The flags of a Field row.
+    pub struct FieldAttributes: u16 {
+        const STATIC = 0x10;
+        const INIT_ONLY = 0x20;
+        /// The field is a compile-time constant with a Constant row and no storage.
+        const LITERAL = 0x40;
+        const NOT_SERIALIZED = 0x80;
+        const HAS_FIELD_RVA = 0x100;
+        const SPECIAL_NAME = 0x200;
+        const RT_SPECIAL_NAME = 0x400;
+        const HAS_FIELD_MARSHAL = 0x1000;
+        const PINVOKE_IMPL = 0x2000;
+        const HAS_DEFAULT = 0x8000;
+    }
+    masks {
+        const FIELD_ACCESS_MASK = 0x7;
+    }
+}
+
+impl FieldAttributes {
+    pub const fn access(self) -> MemberAccess {
+        MemberAccess::from_bits(self.0)
+    }
+}
+
+flags! {
+    /// The flags of a Param row.
+    pub struct ParamAttributes: u16 {
+        const IN = 0x1;
+        const OUT = 0x2;
+        const OPTIONAL = 0x10;
+        const HAS_DEFAULT = 0x1000;
+        const HAS_FIELD_MARSHAL = 0x2000;
+    }
+}
+
+impl ParamAttributes {
+    pub const fn is_in(self) -> bool {
+        self.contains(Self::IN)
+    }
+
+    pub const fn is_out(self) -> bool {
+        self.contains(Self::OUT)
+    }
+
+    pub const fn is_optional(self) -> bool {
+        self.contains(Self::OPTIONAL)
+    }
+
+    pub const fn has_default(self) -> bool {
+        self.contains(Self::HAS_DEFAULT)
+    }
+}
+
+flags! {
+    /// The flags of a Property row.
+    pub struct PropertyAttributes: u16 {
+        const SPECIAL_NAME = 0x200;
+        const RT_SPECIAL_NAME = 0x400;
+        const HAS_DEFAULT = 0x1000;
+    }
+}
+
+impl PropertyAttributes {
+    pub const fn has_default(self) -> bool {
+        self.contains(Self::HAS_DEFAULT)
+    }
+}
+
+flags! {
+    /// The flags of an Event row.
+    pub struct EventAttributes: u16 {
+        const SPECIAL_NAME = 0x200;
+        const RT_SPECIAL_NAME = 0x400;
+    }
+}
+
+flags! {
+    /// The flags of a GenericParam row.
+    pub struct GenericParamAttributes: u16 {
+        /// The `class` constraint.
+        const REFERENCE_TYPE_CONSTRAINT = 0x4;
+        /// The `struct` constraint.
+        const NOT_NULLABLE_VALUE_TYPE_CONSTRAINT = 0x8;
+        /// The `new()` constraint.
+        const DEFAULT_CONSTRUCTOR_CONSTRAINT = 0x10;
+        /// The `allows ref struct` anti-constraint.
+        const ALLOW_BY_REF_LIKE = 0x20;
+    }
+    masks {
+        const VARIANCE_MASK = 0x3;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Variance {
+    None,
+    /// `out`
+    Covariant,
+    /// `in`
+    Contravariant,
+    /// The reserved value `3`.
+    Unknown,
+}
+
+impl GenericParamAttributes {
+    pub const fn variance(self) -> Variance {
+        match self.0 & Self::VARIANCE_MASK.0 {
+            0 => Variance::None,
+            1 => Variance::Covariant,
+            2 => Variance::Contravariant,
+            _ => Variance::Unknown,
+        }
+    }
+}
+
+flags! {
+    /// The implementation flags of a MethodDef row.
+    pub struct MethodImplAttributes: u16 {
+        const UNMANAGED = 0x4;
+        const NO_INLINING = 0x8;
+        const FORWARD_REF = 0x10;
+        const SYNCHRONIZED = 0x20;
+        const NO_OPTIMIZATION = 0x40;
+        const PRESERVE_SIG = 0x80;
+        const AGGRESSIVE_INLINING = 0x100;
+        const AGGRESSIVE_OPTIMIZATION = 0x200;
+        /// The method is implemented inside the runtime.
+        const INTERNAL_CALL = 0x1000;
+    }
+    masks {
+        const CODE_TYPE_MASK = 0x3;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CodeType {
+    Il,
+    Native,
+    /// Reserved for optimized IL.
+    OptIl,
+    /// The runtime provides the implementation, as for delegate methods.
+    Runtime,
+}
+
+impl MethodImplAttributes {
+    pub const fn code_type(self) -> CodeType {
+        match self.0 & Self::CODE_TYPE_MASK.0 {
+            0 => CodeType::Il,
+            1 => CodeType::Native,
+            2 => CodeType::OptIl,
+            _ => CodeType::Runtime,
+        }
+    }
+}
+
+flags! {
+    /// The mapping flags of an ImplMap row, describing how a P/Invoke is bound.
+    pub struct PInvokeAttributes: u16 {
+        /// The entry point name is used exactly as written, without `A` or `W` suffixes.
+        const NO_MANGLE = 0x1;
+        const SUPPORTS_LAST_ERROR = 0x40;
+    }
+    masks {
+        const CHAR_SET_MASK = 0x6;
+        const BEST_FIT_MASK = 0x30;
+        const CALL_CONV_MASK = 0x700;
+        const THROW_ON_UNMAPPABLE_CHAR_MASK = 0x3000;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CharSet {
+    NotSpecified,
+    Ansi,
+    Unicode,
+    Auto,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PInvokeCallingConvention {
+    /// The platform default.
+    Winapi,
+    Cdecl,
+    Stdcall,
+    Thiscall,
+    Fastcall,
+    Unknown(u16),
+}
+
+impl PInvokeAttributes {
+    pub const fn char_set(self) -> CharSet {
+        match self.0 & Self::CHAR_SET_MASK.0 {
+            0 => CharSet::NotSpecified,
+            2 => CharSet::Ansi,
+            4 => CharSet::Unicode,
+            _ => CharSet::Auto,
+        }
+    }
+
+    /// Returns `None` if no calling convention is set, which is invalid but seen in the wild.
+    pub const fn calling_convention(self) -> Option<PInvokeCallingConvention> {
+        Some(match (self.0 & Self::CALL_CONV_MASK.0) >> 8 {
+            0 => return None,
+            1 => PInvokeCallingConvention::Winapi,
+            2 => PInvokeCallingConvention::Cdecl,
+            3 => PInvokeCallingConvention::Stdcall,
+            4 => PInvokeCallingConvention::Thiscall,
+            5 => PInvokeCallingConvention::Fastcall,
+            n => PInvokeCallingConvention::Unknown(n),
+        })
+    }
+
+    /// Returns `None` to use the assembly's setting.
+    pub const fn best_fit(self) -> Option<bool> {
+        tri_state((self.0 & Self::BEST_FIT_MASK.0) >> 4)
+    }
+
+    /// Returns `None` to use the assembly's setting.
+    pub const fn throw_on_unmappable_char(self) -> Option<bool> {
+        tri_state((self.0 & Self::THROW_ON_UNMAPPABLE_CHAR_MASK.0) >> 12)
+    }
+}
+
+/// Decodes a two-bit enabled/disabled field, where neither or both bits set means unspecified.
+const fn tri_state(bits: u16) -> Option<bool> {
+    match bits {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+flags! {
+    /// The flags of a ManifestResource row.
+    pub struct ManifestResourceAttributes: u32 {}
+    masks {
+        const VISIBILITY_MASK = 0x7;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ManifestResourceVisibility {
+    Public,
+    Private,
+    /// Any value other than `1` and `2`, which are the only valid ones.
+    Unknown,
+}
+
+impl ManifestResourceAttributes {
+    pub const fn visibility(self) -> ManifestResourceVisibility {
+        match self.0 & Self::VISIBILITY_MASK.0 {
+            1 => ManifestResourceVisibility::Public,
+            2 => ManifestResourceVisibility::Private,
+            _ => ManifestResourceVisibility::Unknown,
+        }
+    }
+
+    pub const fn is_public(self) -> bool {
+        matches!(self.visibility(), ManifestResourceVisibility::Public)
+    }
+}
+
+flags! {
+    /// The flags of a File row.
+    pub struct FileAttributes: u32 {
+        /// The file is a resource or other non-metadata file. Without it, the file is a module.
+        const CONTAINS_NO_METADATA = 0x1;
+    }
+}
+
+impl FileAttributes {
+    pub const fn contains_metadata(self) -> bool {
+        !self.contains(Self::CONTAINS_NO_METADATA)
+    }
+}
+
+/// The `CALG_*` hash algorithm of an Assembly row, also used for File and AssemblyRef hashes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AssemblyHashAlgorithm {
+    None,
+    Md2,
+    Md4,
+    Md5,
+    Sha1,
+    Mac,
+    Sha256,
+    Sha384,
+    Sha512,
+    Unknown(u32),
+}
+
+impl AssemblyHashAlgorithm {
+    pub const fn raw(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Md2 => 0x8001,
+            Self::Md4 => 0x8002,
+            Self::Md5 => 0x8003,
+            Self::Sha1 => 0x8004,
+            Self::Mac => 0x8005,
+            Self::Sha256 => 0x800C,
+            Self::Sha384 => 0x800D,
+            Self::Sha512 => 0x800E,
+            Self::Unknown(raw) => raw,
+        }
+    }
+
+    /// The digest size in bytes, if the algorithm is a plain hash.
+    pub const fn digest_len(self) -> Option<usize> {
+        match self {
+            Self::Md2 | Self::Md4 | Self::Md5 => Some(16),
+            Self::Sha1 => Some(20),
+            Self::Sha256 => Some(32),
+            Self::Sha384 => Some(48),
+            Self::Sha512 => Some(64),
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for AssemblyHashAlgorithm {
+    fn from(raw: u32) -> Self {
+        match raw {
+            0 => Self::None,
+            0x8001 => Self::Md2,
+            0x8002 => Self::Md4,
+            0x8003 => Self::Md5,
+            0x8004 => Self::Sha1,
+            0x8005 => Self::Mac,
+            0x800C => Self::Sha256,
+            0x800D => Self::Sha384,
+            0x800E => Self::Sha512,
+            raw => Self::Unknown(raw),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        // A public sealed class with sequential layout
+        let ty = TypeAttributes::from_bits(0x00100109);
+        assert_eq!(ty.visibility(), TypeVisibility::Public);
+        assert_eq!(ty.layout(), TypeLayout::Sequential);
+        assert_eq!(ty.string_format(), StringFormat::Ansi);
+        assert!(ty.contains(TypeAttributes::SEALED | TypeAttributes::BEFORE_FIELD_INIT));
+        assert!(!ty.is_nested());
+        assert_eq!(ty.unknown_bits(), 0);
+        assert_eq!(
+            format!("{ty:?}"),
+            "TypeAttributes(SEALED | BEFORE_FIELD_INIT | VISIBILITY_MASK: 0x1 | LAYOUT_MASK: 0x8)"
+        );
+
+        let method = MethodAttributes::from_bits(0x0096);
+        assert_eq!(method.access(), MemberAccess::Public);
+        assert!(method.contains(MethodAttributes::STATIC | MethodAttributes::HIDE_BY_SIG));
+
+        let field = FieldAttributes::from_bits(0x8051);
+        assert_eq!(field.access(), MemberAccess::Private);
+        assert!(field.contains(FieldAttributes::LITERAL | FieldAttributes::HAS_DEFAULT));
+
+        let param = ParamAttributes::from_bits(0x1012);
+        assert!(param.is_out() && param.is_optional() && param.has_default());
+        assert!(!param.is_in());
+
+        let generic = GenericParamAttributes::from_bits(0x11);
+        assert_eq!(generic.variance(), Variance::Covariant);
+        assert!(generic.contains(GenericParamAttributes::DEFAULT_CONSTRUCTOR_CONSTRAINT));
+
+        let imp = MethodImplAttributes::from_bits(0x1003);
+        assert_eq!(imp.code_type(), CodeType::Runtime);
+        assert!(imp.contains(MethodImplAttributes::INTERNAL_CALL));
+
+        // CharSet.Unicode, SetLastError, CallingConvention.Cdecl, BestFitMapping = false
+        let pinvoke = PInvokeAttributes::from_bits(0x0264);
+        assert_eq!(pinvoke.char_set(), CharSet::Unicode);
+        assert_eq!(
+            pinvoke.calling_convention(),
+            Some(PInvokeCallingConvention::Cdecl)
+        );
+        assert_eq!(pinvoke.best_fit(), Some(false));
+        assert_eq!(pinvoke.throw_on_unmappable_char(), None);
+        assert!(pinvoke.contains(PInvokeAttributes::SUPPORTS_LAST_ERROR));
+
+        assert!(ManifestResourceAttributes::from_bits(1).is_public());
+        assert_eq!(
+            ManifestResourceAttributes::from_bits(2).visibility(),
+            ManifestResourceVisibility::Private
+        );
+        assert_eq!(
+            ManifestResourceAttributes::from_bits(3).visibility(),
+            ManifestResourceVisibility::Unknown
+        );
+        assert!(!ManifestResourceAttributes::from_bits(3).is_public());
+        assert!(FileAttributes::from_bits(0).contains_metadata());
+        assert!(!FileAttributes::CONTAINS_NO_METADATA.contains_metadata());
+
+        assert_eq!(
+            AssemblyHashAlgorithm::from(0x800C),
+            AssemblyHashAlgorithm::Sha256
+        );
+        assert_eq!(AssemblyHashAlgorithm::from(0x1234).raw(), 0x1234);
+        assert_eq!(AssemblyHashAlgorithm::Sha1.digest_len(), Some(20));
+    }
+}
@@ -0,0 +1,162 @@
+//! A small inspection tool built on the library, enabled by the `cli` feature.
+//!
+//! Usage: `oxil <headers|sections|imports|exports|resources|debug> <file>`
+
+use oxil::error::ReadImageResult;
+use oxil::image::Image;
+use oxil::pe::debug::DebugDirectory;
+use oxil::pe::export::{ExportDirectory, ExportTarget};
+use oxil::pe::import::{Import, ImportDirectory};
+use oxil::pe::resource::{ResourceDirectory, ResourceName, ResourceNode};
+use std::fs::File;
+use std::io::BufReader;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: oxil <headers|sections|imports|exports|resources|debug> <file>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [command, path] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut data = BufReader::new(file);
+
+    let image = match Image::read(&mut data) {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("{path}: {e:?}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match command.as_str() {
+        "headers" => {
+            headers(&image);
+            Ok(())
+        }
+        "sections" => {
+            sections(&image);
+            Ok(())
+        }
+        "imports" => imports(&image, &mut data),
+        "exports" => exports(&image, &mut data),
+        "resources" => resources(&image, &mut data),
+        "debug" => debug(&image, &mut data),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{path}: {e:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn headers(image: &Image) {
+    let header = &image.header;
+    let cli = &image.cli;
+
+    println!("machine:           {:?}", header.machine);
+    println!("pe32+:             {}", header.pe64);
+    println!("timestamp:         {:#010x}", header.time_date_stamp);
+    println!("image base:        {:#x}", header.image_base);
+    println!("subsystem:         {:?}", header.subsystem);
+    println!("dll flags:         {:?}", header.dll_characteristics);
+    println!(
+        "runtime version:   {}.{}",
+        cli.major_runtime_version, cli.minor_runtime_version
+    );
+    println!("cor flags:         {:?}", cli.flags);
+    println!("entry point:       {:?}", cli.entry_point());
+
+    for warning in &image.warnings {
+        println!("warning:           {warning:?}");
+    }
+}
+
+fn sections(image: &Image) {
+    println!("name      vaddr     vsize     offset    rawsize");
+    for s in &image.header.sections {
+        println!(
+            "{:<8}  {:08x}  {:08x}  {:08x}  {:08x}",
+            s.name, s.virtual_addr, s.virtual_size, s.pointer_to_raw_data, s.size_of_raw_data
+        );
+    }
+}
+
+fn imports(image: &Image, data: &mut BufReader<File>) -> ReadImageResult<()> {
+    for module in ImportDirectory::read(&image.header, data)?.modules {
+        println!("{}", module.name);
+        for function in module.functions {
+            match function.import {
+                Import::Ordinal(ordinal) => println!("  #{ordinal}"),
+                Import::Name { name, .. } => println!("  {name}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn exports(image: &Image, data: &mut BufReader<File>) -> ReadImageResult<()> {
+    let Some(dir) = ExportDirectory::read(&image.header, data)? else {
+        return Ok(());
+    };
+
+    println!("{}", dir.name);
+    for export in dir.exports {
+        let name = export.name.as_deref().unwrap_or("");
+        match export.target {
+            ExportTarget::Rva(rva) => println!("  {:>5}  {rva:08x}  {name}", export.ordinal),
+            ExportTarget::Forwarder(to) => println!("  {:>5}  {name} -> {to}", export.ordinal),
+        }
+    }
+    Ok(())
+}
+
+fn resources(image: &Image, data: &mut BufReader<File>) -> ReadImageResult<()> {
+    fn walk(dir: &ResourceDirectory, depth: usize) {
+        for entry in &dir.entries {
+            let name = match &entry.name {
+                ResourceName::Id(id) => id.to_string(),
+                ResourceName::Name(name) => format!("{name:?}"),
+            };
+            match &entry.node {
+                ResourceNode::Directory(dir) => {
+                    println!("{:indent$}{name}", "", indent = depth * 2);
+                    walk(dir, depth + 1);
+                }
+                ResourceNode::Data(leaf) => println!(
+                    "{:indent$}{name}: {} bytes at {:08x}",
+                    "",
+                    leaf.size,
+                    leaf.rva,
+                    indent = depth * 2
+                ),
+            }
+        }
+    }
+
+    walk(&ResourceDirectory::read(&image.header, data)?, 0);
+    Ok(())
+}
+
+fn debug(image: &Image, data: &mut BufReader<File>) -> ReadImageResult<()> {
+    for entry in DebugDirectory::read(&image.header, data)?.entries {
+        println!("type {:>2}  {:?}", entry.kind, entry.data);
+    }
+    Ok(())
+}
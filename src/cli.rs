@@ -0,0 +1,318 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::flags::flags;
+use crate::pe::{DataDirectory, ImageHeader};
+use crate::read;
+use crate::token::{table, MetadataToken};
+use std::io::{Read, Seek};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliHeader {
+    pub major_runtime_version: u16,
+    pub minor_runtime_version: u16,
+    pub metadata: DataDirectory,
+    pub flags: CorFlags,
+    pub entry_point_token: u32,
+    pub resources: DataDirectory,
+    pub strong_name_signature: DataDirectory,
+    pub code_manager_table: DataDirectory,
+    pub vtable_fixups: DataDirectory,
+    pub export_address_table_jumps: DataDirectory,
+    pub managed_native_header: DataDirectory,
+}
+
+impl CliHeader {
+    /// Reads the CLI header pointed to by an already parsed image header's CLR runtime directory.
+    pub fn read(header: &ImageHeader, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        let dir = header.clr_runtime_header;
+        if dir.rva == 0 || dir.size < 72 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let offset = crate::pe::offset_from(&header.sections, dir.rva)
+            .ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for:
+            goto offset,
+            cb: u32,
+            major_runtime_version: u16,
+            minor_runtime_version: u16,
+            metadata: DataDirectory,
+            flags: CorFlags,
+            entry_point_token: u32,
+            resources: DataDirectory,
+            strong_name_signature: DataDirectory,
+            code_manager_table: DataDirectory,
+            vtable_fixups: DataDirectory,
+            export_address_table_jumps: DataDirectory,
+            managed_native_header: DataDirectory,
+        );
+
+        if cb < 72 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        Ok(CliHeader {
+            major_runtime_version,
+            minor_runtime_version,
+            metadata,
+            flags,
+            entry_point_token,
+            resources,
+            strong_name_signature,
+            code_manager_table,
+            vtable_fixups,
+            export_address_table_jumps,
+            managed_native_header,
+        })
+    }
+}
+
+impl CliHeader {
+    /// Reads an embedded managed resource, given the offset from its ManifestResource row.
+    ///
+    /// Only resources whose Implementation column is null live here; linked resources are stored
+    /// in the File they refer to.
+    pub fn read_resource(
+        &self,
+        header: &ImageHeader,
+        offset: u32,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<u8>> {
+        let dir = self.resources;
+        let start = crate::pe::offset_from(&header.sections, dir.rva)
+            .ok_or(ReadImageError::InvalidImage)?;
+
+        // Each resource is prefixed with its length
+        if offset
+            .checked_add(4)
+            .filter(|&end| end <= dir.size)
+            .is_none()
+        {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        read!(data for:
+            goto start.checked_add(offset).ok_or(ReadImageError::Overflow)?,
+            len: u32,
+        );
+
+        if len > dir.size - offset - 4 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        // Read through `take` so that a bogus length can't allocate more than the file holds
+        let mut buf = vec![];
+        data.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(buf)
+    }
+
+    /// Reads the vtable fixups of a mixed-mode image, which bind native slots to managed
+    /// methods. Returns an empty list if there are none, as for any pure IL image.
+    pub fn vtable_fixups(
+        &self,
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<VTableFixup>> {
+        let dir = self.vtable_fixups;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(vec![]);
+        }
+
+        let offset = crate::pe::offset_from(&header.sections, dir.rva)
+            .ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        let mut raw = Vec::with_capacity((dir.size / 8).min(0x1000) as usize);
+        for _ in 0..dir.size / 8 {
+            read!(data for:
+                rva: u32,
+                count: u16,
+                kind: VTableFixupKind,
+            );
+            raw.push((rva, count, kind));
+        }
+
+        let mut fixups = Vec::with_capacity(raw.len());
+        for (rva, count, kind) in raw {
+            let slots = crate::pe::offset_from(&header.sections, rva)
+                .ok_or(ReadImageError::InvalidImage)?;
+
+            read!(data for: goto slots,);
+
+            // The slots hold tokens on disk and are overwritten with addresses at load time
+            let mut tokens = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let token = read! { data u32 };
+                if kind.contains(VTableFixupKind::SLOTS_64BIT) {
+                    read!(data for: skip 4,);
+                }
+                tokens.push(MetadataToken::from_raw(token));
+            }
+
+            fixups.push(VTableFixup { rva, kind, tokens });
+        }
+
+        Ok(fixups)
+    }
+
+    /// Decodes `entry_point_token`, which is either a token or, for mixed-mode images, an RVA.
+    pub fn entry_point(&self) -> EntryPoint {
+        let raw = self.entry_point_token;
+
+        if self.flags.contains(CorFlags::NATIVE_ENTRYPOINT) {
+            return match raw {
+                0 => EntryPoint::None,
+                rva => EntryPoint::Native(rva),
+            };
+        }
+
+        let token = MetadataToken::from_raw(raw);
+
+        match token.table() {
+            _ if token.is_null() => EntryPoint::None,
+            table::METHOD_DEF => EntryPoint::MethodDef(token.rid()),
+            table::FILE => EntryPoint::File(token.rid()),
+            _ => EntryPoint::Invalid(token),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EntryPoint {
+    /// Libraries usually have no entry point.
+    None,
+    /// The row id of the entry point method.
+    MethodDef(u32),
+    /// The row id of the File that contains the entry point, in multi-module assemblies.
+    File(u32),
+    /// The RVA of a native entry point.
+    Native(u32),
+    /// A token that refers to a table other than MethodDef or File.
+    Invalid(MetadataToken),
+}
+
+impl EntryPoint {
+    /// Returns the entry point as a metadata token, unless it's native or missing.
+    pub fn token(self) -> Option<MetadataToken> {
+        match self {
+            Self::MethodDef(rid) => MetadataToken::from_parts(table::METHOD_DEF, rid),
+            Self::File(rid) => MetadataToken::from_parts(table::FILE, rid),
+            Self::Invalid(token) => Some(token),
+            Self::None | Self::Native(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VTableFixup {
+    /// The RVA of the slot table.
+    pub rva: u32,
+    pub kind: VTableFixupKind,
+    /// The MethodDef tokens the slots are bound to.
+    pub tokens: Vec<MetadataToken>,
+}
+
+flags! {
+    /// The `COR_VTABLE_*` values of a vtable fixup.
+    pub struct VTableFixupKind: u16 {
+        const SLOTS_32BIT = 0x1;
+        const SLOTS_64BIT = 0x2;
+        /// The slots are called from native code through a reverse P/Invoke thunk.
+        const FROM_UNMANAGED = 0x4;
+        const FROM_UNMANAGED_RETAIN_APPDOMAIN = 0x8;
+        const CALL_MOST_DERIVED = 0x10;
+    }
+}
+
+flags! {
+    /// The `COMIMAGE_FLAGS_*` values of the CLI header.
+    pub struct CorFlags: u32 {
+        const IL_ONLY = 0x1;
+        const REQUIRED_32BIT = 0x2;
+        const IL_LIBRARY = 0x4;
+        const STRONG_NAME_SIGNED = 0x8;
+        /// The entry point is an RVA to native code rather than a metadata token.
+        const NATIVE_ENTRYPOINT = 0x10;
+        const TRACK_DEBUG_DATA = 0x10000;
+        const PREFERRED_32BIT = 0x20000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = crate::pe::ImageHeader::read(&mut data).expect("success");
+        let cli = super::CliHeader::read(&header, &mut data).expect("success");
+
+        assert_eq!(cli.major_runtime_version, 2);
+        assert_eq!(cli.entry_point_token, 0x06000001);
+        assert_eq!(cli.flags, super::CorFlags::IL_ONLY);
+        assert_eq!(cli.entry_point(), super::EntryPoint::MethodDef(1));
+        assert_eq!(
+            cli.entry_point().token().expect("token").to_string(),
+            "0x06000001"
+        );
+        assert_eq!(format!("{:?}", cli.flags), "CorFlags(IL_ONLY)");
+
+        // Fake an embedded resource directory in the slack space at the end of .text
+        let mut bytes = include_bytes!("../HelloWorld.dll").to_vec();
+        bytes[0x8C0..0x8C4].copy_from_slice(&5u32.to_le_bytes());
+        bytes[0x8C4..0x8C9].copy_from_slice(b"hello");
+
+        let mut header = header;
+        header.sections[0].virtual_size = 0x800;
+        let mut cli = cli;
+        cli.resources = crate::pe::DataDirectory {
+            rva: 0x26C0,
+            size: 12,
+        };
+
+        let mut data = Cursor::new(&bytes);
+        let resource = cli.read_resource(&header, 0, &mut data).expect("success");
+        assert_eq!(resource, b"hello");
+        assert!(cli.read_resource(&header, 10, &mut data).is_err());
+
+        // A length prefix larger than the file fails without allocating it
+        let mut huge = bytes.clone();
+        huge[0x8C0..0x8C4].copy_from_slice(&0xFFFF_0000u32.to_le_bytes());
+        let mut oversized = cli.clone();
+        oversized.resources.size = u32::MAX;
+        let e = oversized
+            .read_resource(&header, 0, &mut Cursor::new(&huge))
+            .expect_err("failure");
+        assert!(matches!(e.root(), crate::error::ReadImageError::IO(_)));
+
+        // And a vtable fixup with two slots right after it
+        bytes[0x8D0..0x8D4].copy_from_slice(&0x26E0u32.to_le_bytes());
+        bytes[0x8D4..0x8D6].copy_from_slice(&2u16.to_le_bytes());
+        bytes[0x8D6..0x8D8].copy_from_slice(&0x5u16.to_le_bytes());
+        bytes[0x8E0..0x8E4].copy_from_slice(&0x06000001u32.to_le_bytes());
+        bytes[0x8E4..0x8E8].copy_from_slice(&0x06000002u32.to_le_bytes());
+
+        cli.vtable_fixups = crate::pe::DataDirectory {
+            rva: 0x26D0,
+            size: 8,
+        };
+
+        let mut data = Cursor::new(&bytes);
+        let fixups = cli.vtable_fixups(&header, &mut data).expect("success");
+        assert_eq!(fixups.len(), 1);
+        assert!(fixups[0]
+            .kind
+            .contains(super::VTableFixupKind::FROM_UNMANAGED));
+        assert_eq!(fixups[0].tokens[1].raw(), 0x06000002);
+
+        Ok(())
+    }
+}
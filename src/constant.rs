@@ -0,0 +1,151 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+
+/// The `ELEMENT_TYPE_*` values that can appear in the Type column of a Constant row.
+pub mod element_type {
+    pub const BOOLEAN: u8 = 0x02;
+    pub const CHAR: u8 = 0x03;
+    pub const I1: u8 = 0x04;
+    pub const U1: u8 = 0x05;
+    pub const I2: u8 = 0x06;
+    pub const U2: u8 = 0x07;
+    pub const I4: u8 = 0x08;
+    pub const U4: u8 = 0x09;
+    pub const I8: u8 = 0x0A;
+    pub const U8: u8 = 0x0B;
+    pub const R4: u8 = 0x0C;
+    pub const R8: u8 = 0x0D;
+    pub const STRING: u8 = 0x0E;
+    /// Used for null references, with a blob of four zero bytes.
+    pub const CLASS: u8 = 0x12;
+}
+
+/// The default value of a field, parameter or property, decoded from a Constant row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantValue {
+    Boolean(bool),
+    /// A UTF-16 code unit.
+    Char(u16),
+    I1(i8),
+    U1(u8),
+    I2(i16),
+    U2(u16),
+    I4(i32),
+    U4(u32),
+    I8(i64),
+    U8(u64),
+    R4(f32),
+    R8(f64),
+    /// The UTF-16 code units of a string. Metadata doesn't require them to be well-formed, so
+    /// they can include lone surrogates; use [`ConstantValue::string_lossy`] to convert them.
+    String(Vec<u16>),
+    Null,
+}
+
+impl ConstantValue {
+    /// Decodes a constant blob according to its element type. Fails if the blob has the wrong
+    /// size for the type, or the type can't hold a constant.
+    pub fn decode(element_type: u8, blob: &[u8]) -> ReadImageResult<Self> {
+        use element_type::*;
+
+        fn bytes<const N: usize>(blob: &[u8]) -> ReadImageResult<[u8; N]> {
+            blob.try_into().map_err(|_| ReadImageError::InvalidImage)
+        }
+
+        Ok(match element_type {
+            BOOLEAN => Self::Boolean(bytes::<1>(blob)?[0] != 0),
+            CHAR => Self::Char(u16::from_le_bytes(bytes(blob)?)),
+            I1 => Self::I1(i8::from_le_bytes(bytes(blob)?)),
+            U1 => Self::U1(u8::from_le_bytes(bytes(blob)?)),
+            I2 => Self::I2(i16::from_le_bytes(bytes(blob)?)),
+            U2 => Self::U2(u16::from_le_bytes(bytes(blob)?)),
+            I4 => Self::I4(i32::from_le_bytes(bytes(blob)?)),
+            U4 => Self::U4(u32::from_le_bytes(bytes(blob)?)),
+            I8 => Self::I8(i64::from_le_bytes(bytes(blob)?)),
+            U8 => Self::U8(u64::from_le_bytes(bytes(blob)?)),
+            R4 => Self::R4(f32::from_le_bytes(bytes(blob)?)),
+            R8 => Self::R8(f64::from_le_bytes(bytes(blob)?)),
+            STRING => {
+                if blob.len() % 2 != 0 {
+                    return Err(ReadImageError::InvalidImage);
+                }
+                Self::String(
+                    blob.chunks_exact(2)
+                        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                        .collect(),
+                )
+            }
+            CLASS if bytes::<4>(blob)? == [0; 4] => Self::Null,
+            _ => return Err(ReadImageError::InvalidImage),
+        })
+    }
+
+    /// The value of a string constant, with lone surrogates replaced by U+FFFD. Returns `None`
+    /// for other constants.
+    pub fn string_lossy(&self) -> Option<String> {
+        match self {
+            Self::String(units) => Some(String::from_utf16_lossy(units)),
+            _ => None,
+        }
+    }
+
+    /// The element type this value is stored as.
+    pub const fn element_type(&self) -> u8 {
+        use element_type::*;
+
+        match self {
+            Self::Boolean(_) => BOOLEAN,
+            Self::Char(_) => CHAR,
+            Self::I1(_) => I1,
+            Self::U1(_) => U1,
+            Self::I2(_) => I2,
+            Self::U2(_) => U2,
+            Self::I4(_) => I4,
+            Self::U4(_) => U4,
+            Self::I8(_) => I8,
+            Self::U8(_) => U8,
+            Self::R4(_) => R4,
+            Self::R8(_) => R8,
+            Self::String(_) => STRING,
+            Self::Null => CLASS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{element_type, ConstantValue};
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            ConstantValue::decode(element_type::I4, &(-5i32).to_le_bytes()).expect("success"),
+            ConstantValue::I4(-5)
+        );
+        assert_eq!(
+            ConstantValue::decode(element_type::STRING, b"h\0i\0")
+                .expect("success")
+                .string_lossy()
+                .as_deref(),
+            Some("hi")
+        );
+
+        // A lone high surrogate, as C# allows in const strings
+        let lone = ConstantValue::decode(element_type::STRING, b"a\0\x00\xD8").expect("success");
+        assert_eq!(lone, ConstantValue::String(vec![0x61, 0xD800]));
+        assert_eq!(lone.string_lossy().as_deref(), Some("a\u{FFFD}"));
+        assert_eq!(
+            ConstantValue::decode(element_type::CLASS, &[0; 4]).expect("success"),
+            ConstantValue::Null
+        );
+        assert_eq!(
+            ConstantValue::decode(element_type::R8, &1.5f64.to_le_bytes())
+                .expect("success")
+                .element_type(),
+            element_type::R8
+        );
+
+        assert!(ConstantValue::decode(element_type::I4, &[0; 2]).is_err());
+        assert!(ConstantValue::decode(element_type::STRING, &[0; 3]).is_err());
+    }
+}
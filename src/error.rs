@@ -8,6 +8,59 @@ pub enum ReadImageError {
     Utf(std::str::Utf8Error),
     /// The image is not a valid CLR-compatible image.
     InvalidImage,
+    /// An offset or size computed from values in the image doesn't fit in 32 bits.
+    Overflow,
+    /// Another error, annotated with the structure that was being read and its file offset.
+    Context {
+        offset: u64,
+        what: String,
+        source: Box<ReadImageError>,
+    },
+}
+
+impl ReadImageError {
+    /// The error without any context.
+    pub fn root(&self) -> &ReadImageError {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// The file offset of the innermost structure that was being read, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Context { offset, source, .. } => source.offset().or(Some(*offset)),
+            _ => None,
+        }
+    }
+
+    /// The structures that were being read, outermost first, like `["import descriptor 1",
+    /// "thunk table"]`.
+    pub fn path(&self) -> Vec<&str> {
+        let mut path = vec![];
+        let mut e = self;
+        while let Self::Context { what, source, .. } = e {
+            path.push(what.as_str());
+            e = source;
+        }
+        path
+    }
+}
+
+pub(crate) trait Context<T> {
+    /// Annotates an error with the structure being read. `what` is only called on failure.
+    fn context(self, offset: u64, what: impl FnOnce() -> String) -> ReadImageResult<T>;
+}
+
+impl<T> Context<T> for ReadImageResult<T> {
+    fn context(self, offset: u64, what: impl FnOnce() -> String) -> ReadImageResult<T> {
+        self.map_err(|source| ReadImageError::Context {
+            offset,
+            what: what(),
+            source: Box::new(source),
+        })
+    }
 }
 
 impl From<std::io::Error> for ReadImageError {
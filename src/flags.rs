@@ -0,0 +1,121 @@
+/// Defines a transparent newtype over an integer with named bit constants.
+///
+/// Unknown bits are preserved, so no information from the image is lost.
+///
+/// Multi-bit fields that hold an enumerated value rather than independent flags can be listed in
+/// a trailing `masks` block. Their bits count as known, and `Debug` prints their masked value
+/// instead of trying to match them against flag names.
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident: $t:ty {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+        $(
+            masks {
+                $(
+                    $(#[$mask_meta:meta])*
+                    const $mask:ident = $mask_value:expr;
+                )*
+            }
+        )?
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+        pub struct $name($t);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self($value);
+            )*
+            $($(
+                $(#[$mask_meta])*
+                pub const $mask: Self = Self($mask_value);
+            )*)?
+
+            /// Wraps raw bits, including ones without a named constant.
+            pub const fn from_bits(bits: $t) -> Self {
+                Self(bits)
+            }
+
+            /// Returns the raw bits, including ones without a named constant.
+            pub const fn bits(self) -> $t {
+                self.0
+            }
+
+            /// Returns true if every bit in `other` is set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns true if any bit in `other` is set.
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            /// Returns the bits that don't correspond to any named constant.
+            pub const fn unknown_bits(self) -> $t {
+                let known: $t = 0 $(| Self::$flag.0)* $($(| Self::$mask.0)*)?;
+                self.0 & !known
+            }
+
+            // Used by `read!`, so unused for types that only come from table columns
+            #[allow(dead_code)]
+            pub(crate) fn from_le_bytes(bytes: [u8; ::std::mem::size_of::<$t>()]) -> Self {
+                Self(<$t>::from_le_bytes(bytes))
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut first = true;
+                let mut sep = |f: &mut ::std::fmt::Formatter<'_>| {
+                    let s = if first { "" } else { " | " };
+                    first = false;
+                    f.write_str(s)
+                };
+
+                write!(f, "{}(", stringify!($name))?;
+                $(
+                    if Self::$flag.0 != 0 && self.contains(Self::$flag) {
+                        sep(f)?;
+                        f.write_str(stringify!($flag))?;
+                    }
+                )*
+                $($(
+                    if self.0 & Self::$mask.0 != 0 {
+                        sep(f)?;
+                        write!(f, "{}: {:#x}", stringify!($mask), self.0 & Self::$mask.0)?;
+                    }
+                )*)?
+                if self.unknown_bits() != 0 {
+                    sep(f)?;
+                    write!(f, "{:#x}", self.unknown_bits())?;
+                }
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+pub(crate) use flags;
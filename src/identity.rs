@@ -0,0 +1,317 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// An assembly display name, like `System.Runtime, Version=6.0.0.0, Culture=neutral,
+/// PublicKeyToken=b03f5f7f11d50a3a`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssemblyIdentity {
+    pub name: String,
+    pub version: Option<[u16; 4]>,
+    /// `None` if unspecified. A neutral culture is `Some("")`, written as `neutral`.
+    pub culture: Option<String>,
+    /// `None` if unspecified. An unsigned assembly is `Some(None)`, written as `null`.
+    pub public_key_token: Option<Option<[u8; 8]>>,
+    pub processor_architecture: Option<ProcessorArchitecture>,
+    pub retargetable: bool,
+    pub content_type: Option<ContentType>,
+    /// Attributes this type doesn't know about, like `Custom=null`, kept in order so that they
+    /// survive a round trip.
+    pub other: Vec<(String, String)>,
+}
+
+/// The `processorArchitecture` attribute, found in GAC and fusion display names.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProcessorArchitecture {
+    None,
+    Msil,
+    X86,
+    Ia64,
+    Amd64,
+    Arm,
+    Arm64,
+}
+
+impl ProcessorArchitecture {
+    const NAMES: [(Self, &'static str); 7] = [
+        (Self::None, "None"),
+        (Self::Msil, "MSIL"),
+        (Self::X86, "x86"),
+        (Self::Ia64, "IA64"),
+        (Self::Amd64, "AMD64"),
+        (Self::Arm, "Arm"),
+        (Self::Arm64, "Arm64"),
+    ];
+
+    pub const fn name(self) -> &'static str {
+        Self::NAMES[self as usize].1
+    }
+}
+
+/// The `ContentType` attribute. Windows Runtime metadata references use `WindowsRuntime`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ContentType {
+    Default,
+    WindowsRuntime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIdentityError {
+    MissingName,
+    /// A quote or escape sequence wasn't terminated.
+    Malformed,
+    InvalidVersion,
+    InvalidPublicKeyToken,
+    /// A processorArchitecture or ContentType value that isn't defined, with the attribute name.
+    InvalidAttributeValue(String),
+}
+
+impl AssemblyIdentity {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: None,
+            culture: None,
+            public_key_token: None,
+            processor_architecture: None,
+            retargetable: false,
+            content_type: None,
+            other: vec![],
+        }
+    }
+}
+
+impl fmt::Display for AssemblyIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, &self.name)?;
+
+        if let Some([a, b, c, d]) = self.version {
+            write!(f, ", Version={a}.{b}.{c}.{d}")?;
+        }
+
+        match self.culture.as_deref() {
+            Some("") => f.write_str(", Culture=neutral")?,
+            Some(culture) => write!(f, ", Culture={culture}")?,
+            None => {}
+        }
+
+        match self.public_key_token {
+            Some(Some(token)) => {
+                f.write_str(", PublicKeyToken=")?;
+                for b in token {
+                    write!(f, "{b:02x}")?;
+                }
+            }
+            Some(None) => f.write_str(", PublicKeyToken=null")?,
+            None => {}
+        }
+
+        if let Some(arch) = self.processor_architecture {
+            write!(f, ", processorArchitecture={}", arch.name())?;
+        }
+
+        if self.retargetable {
+            f.write_str(", Retargetable=Yes")?;
+        }
+
+        match self.content_type {
+            Some(ContentType::Default) => f.write_str(", ContentType=Default")?,
+            Some(ContentType::WindowsRuntime) => f.write_str(", ContentType=WindowsRuntime")?,
+            None => {}
+        }
+
+        for (key, value) in &self.other {
+            f.write_str(", ")?;
+            write_escaped(f, key)?;
+            f.write_str("=")?;
+            write_escaped(f, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        if matches!(c, ',' | '=' | '\\' | '"' | '\'') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    Ok(())
+}
+
+impl FromStr for AssemblyIdentity {
+    type Err = ParseIdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = split_unescaped(s, ',')?.into_iter();
+
+        let name = parts.next().unwrap_or_default();
+        if name.is_empty() {
+            return Err(ParseIdentityError::MissingName);
+        }
+
+        let mut identity = Self::new(name);
+
+        for part in parts {
+            let (key, value) = part.split_once('=').ok_or(ParseIdentityError::Malformed)?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if key.eq_ignore_ascii_case("Version") {
+                identity.version = Some(parse_version(value)?);
+            } else if key.eq_ignore_ascii_case("Culture") {
+                identity.culture = Some(match value.eq_ignore_ascii_case("neutral") {
+                    true => String::new(),
+                    false => value.to_owned(),
+                });
+            } else if key.eq_ignore_ascii_case("PublicKeyToken") {
+                identity.public_key_token = Some(parse_token(value)?);
+            } else if key.eq_ignore_ascii_case("processorArchitecture") {
+                identity.processor_architecture = Some(
+                    ProcessorArchitecture::NAMES
+                        .iter()
+                        .find(|(_, name)| name.eq_ignore_ascii_case(value))
+                        .map(|(arch, _)| *arch)
+                        .ok_or_else(|| ParseIdentityError::InvalidAttributeValue(key.to_owned()))?,
+                );
+            } else if key.eq_ignore_ascii_case("Retargetable") {
+                identity.retargetable = value.eq_ignore_ascii_case("Yes");
+            } else if key.eq_ignore_ascii_case("ContentType") {
+                identity.content_type = Some(match value {
+                    _ if value.eq_ignore_ascii_case("Default") => ContentType::Default,
+                    _ if value.eq_ignore_ascii_case("WindowsRuntime") => {
+                        ContentType::WindowsRuntime
+                    }
+                    _ => return Err(ParseIdentityError::InvalidAttributeValue(key.to_owned())),
+                });
+            } else {
+                identity.other.push((key.to_owned(), value.to_owned()));
+            }
+        }
+
+        Ok(identity)
+    }
+}
+
+/// Splits on `sep` outside of quotes and backslash escapes, unescaping and trimming each part.
+fn split_unescaped(s: &str, sep: char) -> Result<Vec<String>, ParseIdentityError> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.push(chars.next().ok_or(ParseIdentityError::Malformed)?),
+            '"' | '\'' if quote == Some(c) => quote = None,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            c if c == sep && quote.is_none() => {
+                parts.push(current.trim().to_owned());
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(ParseIdentityError::Malformed);
+    }
+
+    parts.push(current.trim().to_owned());
+    Ok(parts)
+}
+
+fn parse_version(s: &str) -> Result<[u16; 4], ParseIdentityError> {
+    let mut version = [0; 4];
+    let mut count = 0;
+
+    for part in s.split('.') {
+        let slot = version
+            .get_mut(count)
+            .ok_or(ParseIdentityError::InvalidVersion)?;
+        *slot = part
+            .parse()
+            .map_err(|_| ParseIdentityError::InvalidVersion)?;
+        count += 1;
+    }
+
+    if count < 2 {
+        return Err(ParseIdentityError::InvalidVersion);
+    }
+
+    Ok(version)
+}
+
+fn parse_token(s: &str) -> Result<Option<[u8; 8]>, ParseIdentityError> {
+    if s.eq_ignore_ascii_case("null") {
+        return Ok(None);
+    }
+
+    if s.len() != 16 || !s.is_ascii() {
+        return Err(ParseIdentityError::InvalidPublicKeyToken);
+    }
+
+    let mut token = [0; 8];
+    for (i, b) in token.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)
+            .map_err(|_| ParseIdentityError::InvalidPublicKeyToken)?;
+    }
+
+    Ok(Some(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssemblyIdentity, ContentType, ProcessorArchitecture};
+
+    #[test]
+    fn it_works() {
+        let name =
+            "System.Runtime, Version=6.0.0.0, Culture=neutral, PublicKeyToken=b03f5f7f11d50a3a";
+        let identity: AssemblyIdentity = name.parse().expect("success");
+
+        assert_eq!(identity.name, "System.Runtime");
+        assert_eq!(identity.version, Some([6, 0, 0, 0]));
+        assert_eq!(identity.culture.as_deref(), Some(""));
+        assert_eq!(
+            identity.public_key_token,
+            Some(Some([0xb0, 0x3f, 0x5f, 0x7f, 0x11, 0xd5, 0x0a, 0x3a]))
+        );
+        assert_eq!(identity.to_string(), name);
+
+        let escaped: AssemblyIdentity = r"Odd\,Name, PublicKeyToken=null".parse().expect("success");
+        assert_eq!(escaped.name, "Odd,Name");
+        assert_eq!(escaped.to_string(), r"Odd\,Name, PublicKeyToken=null");
+
+        // As found in the GAC and in WinRT and Silverlight references
+        for name in [
+            "System, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089, processorArchitecture=MSIL",
+            "mscorlib, Version=2.0.5.0, Culture=neutral, PublicKeyToken=7cec85d7bea7798e, Retargetable=Yes",
+            "Windows, Version=255.255.255.255, Culture=neutral, PublicKeyToken=null, ContentType=WindowsRuntime",
+            "Microsoft.Build, Version=15.1.0.0, Culture=neutral, PublicKeyToken=b03f5f7f11d50a3a, Custom=null",
+        ] {
+            let identity: AssemblyIdentity = name.parse().expect("success");
+            assert_eq!(identity.to_string(), name);
+        }
+
+        let identity: AssemblyIdentity =
+            "System.Data, Version=4.0.0.0, processorArchitecture=amd64, Custom=null"
+                .parse()
+                .expect("success");
+        assert_eq!(
+            identity.processor_architecture,
+            Some(ProcessorArchitecture::Amd64)
+        );
+        assert_eq!(identity.content_type, None);
+        assert_eq!(identity.other, [("Custom".into(), "null".into())]);
+
+        let identity: AssemblyIdentity = "Windows, ContentType=WindowsRuntime"
+            .parse()
+            .expect("success");
+        assert_eq!(identity.content_type, Some(ContentType::WindowsRuntime));
+
+        assert!("System, processorArchitecture=Z80"
+            .parse::<AssemblyIdentity>()
+            .is_err());
+    }
+}
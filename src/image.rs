@@ -0,0 +1,219 @@
+use crate::cli::{CliHeader, VTableFixupKind};
+use crate::error::Context;
+use crate::error::ReadImageResult;
+use crate::pe::export::{ExportDirectory, ExportTarget};
+use crate::pe::{offset_from, ImageHeader};
+use crate::stream::ForwardReader;
+use crate::token::MetadataToken;
+use crate::warning::{self, Warning};
+use std::io::{Read, Seek, SeekFrom};
+
+/// A managed method exported to native callers, like with ILAsm's `.export` or DllExport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmanagedExport {
+    pub ordinal: u32,
+    pub name: Option<String>,
+    pub method: MetadataToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub header: ImageHeader,
+    pub cli: CliHeader,
+    /// Oddities noticed while reading that weren't severe enough to fail.
+    pub warnings: Vec<Warning>,
+}
+
+impl Image {
+    pub fn read(data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        let header = ImageHeader::read(data)?;
+        Self::from_header(header, data)
+    }
+
+    /// Reads the image in a single forward pass, for sources that can't seek like pipes and
+    /// network streams. See [`ForwardReader`] for the limitations.
+    pub fn read_forward(data: impl Read) -> ReadImageResult<Self> {
+        Self::read(&mut ForwardReader::new(data))
+    }
+
+    /// Continues reading from an image header that was already parsed, skipping the PE layer.
+    pub fn from_header(
+        header: ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Self> {
+        let offset = crate::pe::offset_from(&header.sections, header.clr_runtime_header.rva);
+        let cli = CliHeader::read(&header, data)
+            .context(offset.unwrap_or(0) as u64, || "CLI header".into())?;
+        let warnings = warning::check(&header, &cli);
+        Ok(Self {
+            header,
+            cli,
+            warnings,
+        })
+    }
+
+    /// Reads the contents of the first section with the given name, like `.text` or `.rsrc`.
+    /// Returns `None` if there's no such section. Use [`SectionHeader::read_data`] to read a
+    /// section by index.
+    ///
+    /// [`SectionHeader::read_data`]: crate::pe::SectionHeader::read_data
+    pub fn section_data(
+        &self,
+        name: &str,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Vec<u8>>> {
+        match self
+            .header
+            .sections
+            .iter()
+            .find(|s| s.name.as_str() == name)
+        {
+            Some(section) => section.read_data(data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Maps exports to the managed methods they call. Export stubs jump through a vtable fixup
+    /// slot, which is matched back to its MethodDef token. Exports that aren't such stubs are
+    /// skipped, so this is empty for pure IL images.
+    ///
+    /// Only the x86 (`jmp [slot]`) and x64 (`mov rax, [slot]; jmp rax`) stubs emitted by ILAsm
+    /// are recognized.
+    pub fn unmanaged_exports(
+        &self,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<UnmanagedExport>> {
+        let fixups = self.cli.vtable_fixups(&self.header, data)?;
+        if fixups.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let exports = match ExportDirectory::read(&self.header, data)? {
+            Some(dir) => dir.exports,
+            None => return Ok(vec![]),
+        };
+
+        let mut unmanaged = vec![];
+        for export in exports {
+            let ExportTarget::Rva(rva) = export.target else {
+                continue;
+            };
+            let Some(slot) = self.stub_target(rva, data)? else {
+                continue;
+            };
+
+            let method = fixups.iter().find_map(|fixup| {
+                let size = match fixup.kind.contains(VTableFixupKind::SLOTS_64BIT) {
+                    true => 8,
+                    false => 4,
+                };
+                let delta = slot.checked_sub(fixup.rva)?;
+                match delta % size {
+                    0 => fixup.tokens.get((delta / size) as usize).copied(),
+                    _ => None,
+                }
+            });
+
+            if let Some(method) = method {
+                unmanaged.push(UnmanagedExport {
+                    ordinal: export.ordinal,
+                    name: export.name,
+                    method,
+                });
+            }
+        }
+
+        Ok(unmanaged)
+    }
+
+    /// Decodes an export stub and returns the RVA of the slot it jumps through.
+    fn stub_target(&self, rva: u32, data: &mut (impl Read + Seek)) -> ReadImageResult<Option<u32>> {
+        let Some(offset) = offset_from(&self.header.sections, rva) else {
+            return Ok(None);
+        };
+
+        data.seek(SeekFrom::Start(offset as u64))?;
+        let mut stub = [0; 12];
+        let mut len = 0;
+        while len < stub.len() {
+            match data.read(&mut stub[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+
+        let va = match stub[..len] {
+            [0xFF, 0x25, a, b, c, d, ..] if !self.header.pe64 => {
+                u32::from_le_bytes([a, b, c, d]) as u64
+            }
+            [0x48, 0xA1, a, b, c, d, e, f, g, h, 0xFF, 0xE0] if self.header.pe64 => {
+                u64::from_le_bytes([a, b, c, d, e, f, g, h])
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(va
+            .checked_sub(self.header.image_base)
+            .and_then(|rva| u32::try_from(rva).ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::DataDirectory;
+
+    #[test]
+    fn it_works() {
+        let mut data = include_bytes!("../HelloWorld.dll").as_ref();
+        let mut image = super::Image::read(&mut Cursor::new(&mut data)).expect("success");
+        assert!(image
+            .unmanaged_exports(&mut Cursor::new(&mut data))
+            .expect("success")
+            .is_empty());
+
+        // Fake an ILAsm-style export in the slack space at the end of .text
+        image.header.sections[0].virtual_size = 0x800;
+        let mut bytes = include_bytes!("../HelloWorld.dll").to_vec();
+        let mut put = |rva: usize, value: &[u8]| {
+            let at = rva - 0x2000 + 0x200;
+            bytes[at..at + value.len()].copy_from_slice(value);
+        };
+
+        // Export directory: name, base, 1 function, 1 name, and the three tables
+        put(0x26CC, &0x26F4u32.to_le_bytes());
+        put(0x26D0, &1u32.to_le_bytes());
+        put(0x26D4, &1u32.to_le_bytes());
+        put(0x26D8, &1u32.to_le_bytes());
+        put(0x26DC, &0x26E8u32.to_le_bytes());
+        put(0x26E0, &0x26ECu32.to_le_bytes());
+        put(0x26E4, &0x26F0u32.to_le_bytes());
+        put(0x26E8, &0x2710u32.to_le_bytes());
+        put(0x26EC, &0x2700u32.to_le_bytes());
+        put(0x26F4, b"a.dll\0");
+        put(0x2700, b"Foo\0");
+
+        // jmp [0x402720], through the slot of a one-entry vtable fixup
+        put(0x2710, &[0xFF, 0x25, 0x20, 0x27, 0x40, 0x00]);
+        put(0x2720, &0x06000001u32.to_le_bytes());
+        put(0x2728, &0x2720u32.to_le_bytes());
+        put(0x272C, &[1, 0, 5, 0]);
+
+        image.header.export = DataDirectory {
+            rva: 0x26C0,
+            size: 40,
+        };
+        image.cli.vtable_fixups = DataDirectory {
+            rva: 0x2728,
+            size: 8,
+        };
+
+        let exports = image
+            .unmanaged_exports(&mut Cursor::new(&bytes))
+            .expect("success");
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].name.as_deref(), Some("Foo"));
+        assert_eq!(exports[0].method.raw(), 0x06000001);
+    }
+}
@@ -1,5 +1,19 @@
-mod error;
-mod pe;
+pub mod attributes;
+pub mod cli;
+pub mod constant;
+pub mod error;
+mod flags;
+pub mod identity;
+pub mod image;
+pub mod pdb;
+pub mod pe;
+pub mod r2r;
+pub mod resource_set;
+pub mod security;
+pub mod stream;
+pub mod token;
+pub mod type_name;
+pub mod warning;
 
 macro_rules! read {
     ($data:ident for: $($etc:tt)*) => {
@@ -44,8 +58,6 @@ macro_rules! read {
 
 pub(crate) use read;
 
-use arrayvec::ArrayString;
-
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -55,7 +67,7 @@ mod tests {
         let mut data = include_bytes!("../HelloWorld.dll").as_ref();
         let mut data = Cursor::new(&mut data);
 
-        dbg!();
+        dbg!(crate::image::Image::read(&mut data).expect("success"));
 
         Ok(())
     }
@@ -0,0 +1,119 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::debug::{DebugData, DebugDirectory};
+use crate::read;
+use std::io::{Read, Seek};
+
+/// The identity of a Portable PDB, stored in the first 20 bytes of its #Pdb stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PdbId {
+    pub guid: [u8; 16],
+    pub stamp: u32,
+}
+
+impl PdbId {
+    /// Reads the id of a standalone Portable PDB file.
+    pub fn read(mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        read!(data for:
+            goto 0,
+            signature: u32,
+            skip 8,
+            version_len: u32,
+            skip version_len,
+            skip 2,
+            streams: u16,
+        );
+
+        if signature != 0x424A5342 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        for _ in 0..streams {
+            read!(data for:
+                offset: u32,
+                _size: u32,
+            );
+
+            // Stream names are null-terminated and padded to a multiple of 4 bytes
+            let mut name = [0; 32];
+            let mut len = 0;
+            loop {
+                let mut chunk = [0; 4];
+                data.read_exact(&mut chunk)?;
+                if len + 4 > name.len() {
+                    return Err(ReadImageError::InvalidImage);
+                }
+                name[len..len + 4].copy_from_slice(&chunk);
+                len += 4;
+                if chunk.contains(&0) {
+                    break;
+                }
+            }
+
+            if name.starts_with(b"#Pdb\0") {
+                read!(data for: goto offset,);
+
+                let mut guid = [0; 16];
+                data.read_exact(&mut guid)?;
+                let stamp = read! { data u32 };
+
+                return Ok(Self { guid, stamp });
+            }
+        }
+
+        Err(ReadImageError::InvalidImage)
+    }
+
+    /// Returns true if the image's Portable PDB CodeView entry refers to this PDB.
+    pub fn matches(&self, debug: &DebugDirectory) -> bool {
+        debug.entries.iter().any(|e| match &e.data {
+            DebugData::CodeView(cv) if e.is_portable_pdb() => {
+                cv.guid == self.guid && e.time_date_stamp == self.stamp
+            }
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::debug::DebugDirectory;
+    use crate::pe::ImageHeader;
+
+    fn fake_pdb(guid: [u8; 16], stamp: u32) -> Vec<u8> {
+        let mut pdb = vec![];
+        pdb.extend(0x424A5342u32.to_le_bytes());
+        pdb.extend([1, 0, 1, 0, 0, 0, 0, 0]);
+        pdb.extend(12u32.to_le_bytes());
+        pdb.extend(b"PDB v1.0\0\0\0\0");
+        pdb.extend([0, 0, 1, 0]);
+        pdb.extend(48u32.to_le_bytes());
+        pdb.extend(20u32.to_le_bytes());
+        pdb.extend(b"#Pdb\0\0\0\0");
+        pdb.extend(guid);
+        pdb.extend(stamp.to_le_bytes());
+        pdb
+    }
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = ImageHeader::read(&mut data).expect("success");
+        let debug = DebugDirectory::read(&header, &mut data).expect("success");
+        let cv = debug.code_view().expect("codeview entry");
+
+        let pdb = fake_pdb(cv.guid, debug.entries[0].time_date_stamp);
+        let id = super::PdbId::read(&mut Cursor::new(pdb)).expect("success");
+        assert!(id.matches(&debug));
+
+        let pdb = fake_pdb([0; 16], debug.entries[0].time_date_stamp);
+        let id = super::PdbId::read(&mut Cursor::new(pdb)).expect("success");
+        assert!(!id.matches(&debug));
+
+        Ok(())
+    }
+}
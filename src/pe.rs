@@ -1,68 +1,88 @@
+pub mod certificate;
+pub mod checksum;
+pub mod debug;
+pub mod export;
+pub mod import;
+pub mod load_config;
+pub mod resource;
+pub mod strip;
+pub mod symbol;
+pub mod tls;
+pub mod version;
+
 use arrayvec::ArrayString;
 
 use crate::error::ReadImageError;
 use crate::error::ReadImageResult;
+use crate::flags::flags;
 use crate::read;
 use std::io::{Read, Seek};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImageHeader {
     // COFF file header
-    number_of_sections: u16,
-    time_date_stamp: u32,
-    pointer_to_symbol_table: u32,
-    number_of_symbols: u32,
-    size_of_optional_header: u16,
-    characteristics: u16,
+    pub machine: Machine,
+    pub number_of_sections: u16,
+    pub time_date_stamp: u32,
+    pub pointer_to_symbol_table: u32,
+    pub number_of_symbols: u32,
+    pub size_of_optional_header: u16,
+    pub characteristics: u16,
 
     // Optional Header Standard Fields
-    pe64: bool,
-    major_linker_version: u8,
-    minor_linker_version: u8,
-    size_of_code: u32,
-    size_of_initialized_data: u32,
-    size_of_uninitialized_data: u32,
-    address_of_entry_point: u32,
-    base_of_code: u32,
+    pub pe64: bool,
+    pub major_linker_version: u8,
+    pub minor_linker_version: u8,
+    pub size_of_code: u32,
+    pub size_of_initialized_data: u32,
+    pub size_of_uninitialized_data: u32,
+    pub address_of_entry_point: u32,
+    pub base_of_code: u32,
 
     // Optional Header Windows-Specific Fields
-    base_of_data: Option<u32>,
-    image_base: u64,
-    section_alignment: u32,
-    file_alignment: u32,
-    major_operating_system_version: u16,
-    minor_operating_system_version: u16,
-    major_image_version: u16,
-    minor_image_version: u16,
-    major_subsystem_version: u16,
-    minor_subsystem_version: u16,
-    size_of_image: u32,
-    size_of_headers: u32,
-    subsystem: u16,
-    dll_characteristics: u16,
-    size_of_stack_reserve: u64,
-    size_of_stack_commit: u64,
-    size_of_heap_reserve: u64,
-    size_of_heap_commit: u64,
+    pub base_of_data: Option<u32>,
+    pub image_base: u64,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub major_operating_system_version: u16,
+    pub minor_operating_system_version: u16,
+    pub major_image_version: u16,
+    pub minor_image_version: u16,
+    pub major_subsystem_version: u16,
+    pub minor_subsystem_version: u16,
+    /// Reserved, must be zero.
+    pub win32_version_value: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub check_sum: u32,
+    pub subsystem: Subsystem,
+    pub dll_characteristics: DllCharacteristics,
+    pub size_of_stack_reserve: u64,
+    pub size_of_stack_commit: u64,
+    pub size_of_heap_reserve: u64,
+    pub size_of_heap_commit: u64,
+    /// Reserved, must be zero.
+    pub loader_flags: u32,
 
     // Optional Header Data Directories
-    export: DataDirectory,
-    import: DataDirectory,
-    resource: DataDirectory,
-    exception: DataDirectory,
-    certificate: DataDirectory,
-    base_relocation: DataDirectory,
-    debug: DataDirectory,
-    global_ptr: DataDirectory,
-    tls: DataDirectory,
-    load_config: DataDirectory,
-    bound_import: DataDirectory,
-    iat: DataDirectory,
-    delay_import_descriptor: DataDirectory,
-    clr_runtime_header: DataDirectory,
+    pub export: DataDirectory,
+    pub import: DataDirectory,
+    pub resource: DataDirectory,
+    pub exception: DataDirectory,
+    pub certificate: DataDirectory,
+    pub base_relocation: DataDirectory,
+    pub debug: DataDirectory,
+    pub global_ptr: DataDirectory,
+    pub tls: DataDirectory,
+    pub load_config: DataDirectory,
+    pub bound_import: DataDirectory,
+    pub iat: DataDirectory,
+    pub delay_import_descriptor: DataDirectory,
+    pub clr_runtime_header: DataDirectory,
 
     // Section headers
-    sections: Vec<SectionHeader>,
+    pub sections: Vec<SectionHeader>,
 }
 
 impl ImageHeader {
@@ -75,7 +95,7 @@ impl ImageHeader {
             // DOS header
             goto 0x3C,
             pe_signature_offset: u32,
-            goto pe_signature_offset + 4,
+            goto pe_signature_offset.checked_add(4).ok_or(ReadImageError::Overflow)?,
 
             // COFF file header
             machine: u16,
@@ -97,10 +117,6 @@ impl ImageHeader {
             base_of_code: u32,
         );
 
-        if machine != 0x14C {
-            return Err(ReadImageError::InvalidImage);
-        }
-
         let pe64 = match magic {
             0x10B => false,
             0x20B => true,
@@ -124,12 +140,12 @@ impl ImageHeader {
             minor_image_version: u16,
             major_subsystem_version: u16,
             minor_subsystem_version: u16,
-            skip 4,
+            win32_version_value: u32,
             size_of_image: u32,
             size_of_headers: u32,
-            skip 4, // ignore checksum
+            check_sum: u32,
             subsystem: u16,
-            dll_characteristics: u16,
+            dll_characteristics: DllCharacteristics,
         );
 
         let (
@@ -146,7 +162,7 @@ impl ImageHeader {
         };
 
         read!(data for:
-            skip 4,
+            loader_flags: u32,
             number_of_rva_and_sizes: u32,
 
             // Optional Header Data Directories
@@ -182,7 +198,7 @@ impl ImageHeader {
                 size_of_raw_data: u32,
                 pointer_to_raw_data: u32,
                 skip 12,
-                characteristics: u32,
+                characteristics: SectionCharacteristics,
             );
             sections.push(SectionHeader {
                 name,
@@ -195,6 +211,7 @@ impl ImageHeader {
         }
 
         Ok(ImageHeader {
+            machine: Machine::from(machine),
             number_of_sections,
             time_date_stamp,
             pointer_to_symbol_table,
@@ -219,14 +236,17 @@ impl ImageHeader {
             minor_image_version,
             major_subsystem_version,
             minor_subsystem_version,
+            win32_version_value,
             size_of_image,
             size_of_headers,
-            subsystem,
+            check_sum,
+            subsystem: Subsystem::from(subsystem),
             dll_characteristics,
             size_of_stack_reserve,
             size_of_stack_commit,
             size_of_heap_reserve,
             size_of_heap_commit,
+            loader_flags,
             export,
             import,
             resource,
@@ -244,31 +264,313 @@ impl ImageHeader {
             sections,
         })
     }
+
+    /// Returns true if `time_date_stamp` isn't a time. Deterministic builds store part of a
+    /// content hash instead, with the high bit set so it can't be mistaken for a date before 2038.
+    pub fn has_pseudo_timestamp(&self) -> bool {
+        self.time_date_stamp & 0x8000_0000 != 0
+    }
+
+    /// The link time, if `time_date_stamp` holds one. Returns `None` for zero and for
+    /// deterministic pseudo-timestamps.
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        match self.time_date_stamp {
+            0 => None,
+            _ if self.has_pseudo_timestamp() => None,
+            secs => Some(UNIX_EPOCH + Duration::from_secs(secs as u64)),
+        }
+    }
+}
+
+/// The target CPU of an image. IL-only images are usually `I386` regardless of where they run.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Machine {
+    I386,
+    Amd64,
+    Arm,
+    ArmNt,
+    Arm64,
+    Ia64,
+    Unknown(u16),
+}
+
+impl Machine {
+    pub const fn raw(self) -> u16 {
+        match self {
+            Self::I386 => 0x14C,
+            Self::Amd64 => 0x8664,
+            Self::Arm => 0x1C0,
+            Self::ArmNt => 0x1C4,
+            Self::Arm64 => 0xAA64,
+            Self::Ia64 => 0x200,
+            Self::Unknown(raw) => raw,
+        }
+    }
+}
+
+impl From<u16> for Machine {
+    fn from(raw: u16) -> Self {
+        match raw {
+            0x14C => Self::I386,
+            0x8664 => Self::Amd64,
+            0x1C0 => Self::Arm,
+            0x1C4 => Self::ArmNt,
+            0xAA64 => Self::Arm64,
+            0x200 => Self::Ia64,
+            raw => Self::Unknown(raw),
+        }
+    }
+}
+
+/// The subsystem required to run an image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Unspecified,
+    Native,
+    WindowsGui,
+    WindowsCui,
+    Os2Cui,
+    PosixCui,
+    NativeWindows,
+    WindowsCeGui,
+    EfiApplication,
+    EfiBootServiceDriver,
+    EfiRuntimeDriver,
+    EfiRom,
+    Xbox,
+    WindowsBootApplication,
+    Unknown(u16),
+}
+
+impl Subsystem {
+    pub const fn raw(self) -> u16 {
+        match self {
+            Self::Unspecified => 0,
+            Self::Native => 1,
+            Self::WindowsGui => 2,
+            Self::WindowsCui => 3,
+            Self::Os2Cui => 5,
+            Self::PosixCui => 7,
+            Self::NativeWindows => 8,
+            Self::WindowsCeGui => 9,
+            Self::EfiApplication => 10,
+            Self::EfiBootServiceDriver => 11,
+            Self::EfiRuntimeDriver => 12,
+            Self::EfiRom => 13,
+            Self::Xbox => 14,
+            Self::WindowsBootApplication => 16,
+            Self::Unknown(raw) => raw,
+        }
+    }
+}
+
+impl From<u16> for Subsystem {
+    fn from(raw: u16) -> Self {
+        match raw {
+            0 => Self::Unspecified,
+            1 => Self::Native,
+            2 => Self::WindowsGui,
+            3 => Self::WindowsCui,
+            5 => Self::Os2Cui,
+            7 => Self::PosixCui,
+            8 => Self::NativeWindows,
+            9 => Self::WindowsCeGui,
+            10 => Self::EfiApplication,
+            11 => Self::EfiBootServiceDriver,
+            12 => Self::EfiRuntimeDriver,
+            13 => Self::EfiRom,
+            14 => Self::Xbox,
+            16 => Self::WindowsBootApplication,
+            raw => Self::Unknown(raw),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct DataDirectory {
-    rva: u32,
-    size: u32,
+    pub rva: u32,
+    pub size: u32,
 }
 
 impl DataDirectory {
-    fn from_le_bytes(bytes: [u8; 8]) -> Self {
+    pub(crate) fn from_le_bytes(bytes: [u8; 8]) -> Self {
         Self {
             rva: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
             size: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
         }
     }
+
+    /// Returns true if `rva` falls inside the directory.
+    pub fn contains(&self, rva: u32) -> bool {
+        rva.checked_sub(self.rva)
+            .is_some_and(|delta| delta < self.size)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SectionHeader {
-    name: ArrayString<8>,
-    virtual_size: u32,
-    virtual_addr: u32,
-    size_of_raw_data: u32,
-    pointer_to_raw_data: u32,
-    characteristics: u32,
+    pub name: ArrayString<8>,
+    pub virtual_size: u32,
+    pub virtual_addr: u32,
+    pub size_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub characteristics: SectionCharacteristics,
+}
+
+impl SectionHeader {
+    /// The size of the section once loaded. Some linkers leave the virtual size at zero, in which
+    /// case the raw size is used.
+    pub fn virtual_extent(&self) -> u32 {
+        match self.virtual_size {
+            0 => self.size_of_raw_data,
+            virtual_size => virtual_size,
+        }
+    }
+
+    /// Returns true if `rva` falls inside the loaded section, even if it's in the zero-filled
+    /// tail that has no file data.
+    pub fn contains_rva(&self, rva: u32) -> bool {
+        rva.checked_sub(self.virtual_addr)
+            .is_some_and(|delta| delta < self.virtual_extent())
+    }
+
+    /// Returns true if the file offset falls inside the section's raw data.
+    pub fn contains_offset(&self, offset: u32) -> bool {
+        offset
+            .checked_sub(self.pointer_to_raw_data)
+            .is_some_and(|delta| delta < self.size_of_raw_data)
+    }
+
+    /// The number of bytes at the start of the section that are both loaded and backed by file
+    /// data. Raw data is padded to the file alignment, so it's cut off at the virtual size when
+    /// that's smaller. A section whose virtual size is larger is zero-filled in memory past this.
+    ///
+    /// Only this part of a section can be converted between RVAs and file offsets.
+    pub fn mapped_size(&self) -> u32 {
+        self.virtual_extent().min(self.size_of_raw_data)
+    }
+
+    /// Reads the section's bytes as stored in the file, up to [`SectionHeader::mapped_size`].
+    /// The zero-filled tail of a section whose virtual size is larger isn't materialized here.
+    pub fn read_data(&self, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Vec<u8>> {
+        let len = self.mapped_size();
+
+        read!(data for: goto self.pointer_to_raw_data,);
+
+        // Read through `take` so that a bogus size can't allocate more than the file holds
+        let mut buf = vec![];
+        data.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(buf)
+    }
+}
+
+flags! {
+    /// The `IMAGE_DLLCHARACTERISTICS_*` values of the optional header.
+    pub struct DllCharacteristics: u16 {
+        const HIGH_ENTROPY_VA = 0x20;
+        const DYNAMIC_BASE = 0x40;
+        const FORCE_INTEGRITY = 0x80;
+        const NX_COMPAT = 0x100;
+        const NO_ISOLATION = 0x200;
+        const NO_SEH = 0x400;
+        const NO_BIND = 0x800;
+        const APPCONTAINER = 0x1000;
+        const WDM_DRIVER = 0x2000;
+        const GUARD_CF = 0x4000;
+        const TERMINAL_SERVER_AWARE = 0x8000;
+    }
+}
+
+flags! {
+    /// The `IMAGE_SCN_*` values of a section header.
+    pub struct SectionCharacteristics: u32 {
+        const TYPE_NO_PAD = 0x8;
+        const CNT_CODE = 0x20;
+        const CNT_INITIALIZED_DATA = 0x40;
+        const CNT_UNINITIALIZED_DATA = 0x80;
+        const LNK_INFO = 0x200;
+        const LNK_REMOVE = 0x800;
+        const LNK_COMDAT = 0x1000;
+        const GPREL = 0x8000;
+        const LNK_NRELOC_OVFL = 0x0100_0000;
+        const MEM_DISCARDABLE = 0x0200_0000;
+        const MEM_NOT_CACHED = 0x0400_0000;
+        const MEM_NOT_PAGED = 0x0800_0000;
+        const MEM_SHARED = 0x1000_0000;
+        const MEM_EXECUTE = 0x2000_0000;
+        const MEM_READ = 0x4000_0000;
+        const MEM_WRITE = 0x8000_0000;
+    }
+}
+
+impl SectionCharacteristics {
+    /// Returns the `IMAGE_SCN_ALIGN_*` alignment in bytes, which is only meaningful in object
+    /// files.
+    pub const fn alignment(self) -> Option<u32> {
+        match (self.0 >> 20) & 0xF {
+            0 | 0xF => None,
+            n => Some(1 << (n - 1)),
+        }
+    }
+}
+
+/// Converts a relative virtual address to a file offset using the section table.
+///
+/// Returns `None` if no section maps the address to file data. See
+/// [`SectionHeader::mapped_size`].
+pub fn offset_from(sections: &[SectionHeader], rva: u32) -> Option<u32> {
+    offset_range_from(sections, rva, 1)
+}
+
+/// Converts a file offset back to an RVA. Returns `None` for offsets outside any section, like
+/// the headers, file alignment padding past the virtual size, or overlay data.
+pub fn rva_from(sections: &[SectionHeader], offset: u32) -> Option<u32> {
+    sections.iter().find_map(|s| {
+        let delta = offset.checked_sub(s.pointer_to_raw_data)?;
+        if delta < s.mapped_size() {
+            s.virtual_addr.checked_add(delta)
+        } else {
+            None
+        }
+    })
+}
+
+/// Like [`offset_from`], but also checks that all `len` bytes starting at `rva` are backed by
+/// file data in the same section.
+pub fn offset_range_from(sections: &[SectionHeader], rva: u32, len: u32) -> Option<u32> {
+    sections.iter().find_map(|s| {
+        let delta = rva.checked_sub(s.virtual_addr)?;
+        if delta.checked_add(len)? <= s.mapped_size() {
+            s.pointer_to_raw_data.checked_add(delta)
+        } else {
+            None
+        }
+    })
+}
+
+/// Reads a null-terminated string at a file offset, as used for import and export names.
+pub(crate) fn read_c_str(
+    offset: u32,
+    mut data: &mut (impl Read + Seek),
+) -> ReadImageResult<String> {
+    read!(data for: goto offset,);
+
+    let mut bytes = vec![];
+    loop {
+        let b = read! { data u8 };
+        if b == 0 {
+            break;
+        }
+        if bytes.len() >= 4096 {
+            return Err(ReadImageError::InvalidImage);
+        }
+        bytes.push(b);
+    }
+
+    Ok(String::from_utf8(bytes).map_err(|e| e.utf8_error())?)
 }
 
 #[cfg(test)]
@@ -280,7 +582,56 @@ mod tests {
         let mut data = include_bytes!("../HelloWorld.dll").as_ref();
         let mut data = Cursor::new(&mut data);
 
-        dbg!(super::ImageHeader::read(&mut data).expect("success"));
+        let header = dbg!(super::ImageHeader::read(&mut data).expect("success"));
+
+        assert_eq!(header.machine, super::Machine::I386);
+        assert_eq!(header.subsystem, super::Subsystem::WindowsCui);
+        assert!(header.dll_characteristics.contains(
+            super::DllCharacteristics::DYNAMIC_BASE | super::DllCharacteristics::NX_COMPAT
+        ));
+        assert!(header.sections[0].characteristics.contains(
+            super::SectionCharacteristics::CNT_CODE | super::SectionCharacteristics::MEM_EXECUTE
+        ));
+
+        let text = header.sections[0].read_data(&mut data).expect("success");
+        assert_eq!(text.len(), 1720);
+        assert_eq!(&text[8..12], &0x48u32.to_le_bytes());
+
+        // Raw data larger than the file fails without allocating it
+        let mut bogus = header.sections[0];
+        bogus.virtual_size = u32::MAX;
+        bogus.size_of_raw_data = u32::MAX;
+        assert!(matches!(
+            bogus.read_data(&mut data),
+            Err(super::ReadImageError::IO(_))
+        ));
+
+        assert_eq!(super::offset_from(&header.sections, 0x2008), Some(0x208));
+        assert_eq!(super::rva_from(&header.sections, 0x208), Some(0x2008));
+        assert_eq!(super::rva_from(&header.sections, 0x8C0), None);
+        assert_eq!(super::offset_from(&header.sections, 0x26B7), Some(0x8B7));
+        assert_eq!(super::offset_from(&header.sections, 0x26C0), None);
+        assert_eq!(header.sections[0].mapped_size(), 0x6B8);
+        assert_eq!(
+            super::offset_range_from(&header.sections, 0x2000, 1720),
+            Some(0x200)
+        );
+        assert_eq!(
+            super::offset_range_from(&header.sections, 0x2000, 1721),
+            None
+        );
+        assert!(header.sections[0].contains_rva(0x2008));
+        assert!(header.clr_runtime_header.contains(0x2008));
+
+        assert!(header.has_pseudo_timestamp());
+        assert_eq!(header.timestamp(), None);
+
+        let mut header = header;
+        header.time_date_stamp = 1_600_000_000;
+        assert_eq!(
+            header.timestamp(),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000))
+        );
 
         Ok(())
     }
@@ -0,0 +1,142 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::ImageHeader;
+use crate::read;
+use std::io::{Read, Seek};
+
+pub const WIN_CERT_REVISION_1_0: u16 = 0x0100;
+pub const WIN_CERT_REVISION_2_0: u16 = 0x0200;
+
+pub const WIN_CERT_TYPE_X509: u16 = 1;
+/// An Authenticode signature, stored as a PKCS#7 SignedData blob.
+pub const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 2;
+pub const WIN_CERT_TYPE_TS_STACK_SIGNED: u16 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateTable {
+    pub certificates: Vec<Certificate>,
+}
+
+/// A WIN_CERTIFICATE entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    pub revision: u16,
+    pub certificate_type: u16,
+    pub data: Vec<u8>,
+}
+
+impl CertificateTable {
+    pub fn read(header: &ImageHeader, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        // This directory's address is a file offset; the table isn't mapped into memory
+        let dir = header.certificate;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(Self {
+                certificates: vec![],
+            });
+        }
+
+        let end = dir
+            .rva
+            .checked_add(dir.size)
+            .ok_or(ReadImageError::InvalidImage)?;
+        let mut offset = dir.rva;
+        let mut certificates = vec![];
+
+        while end - offset >= 8 {
+            read!(data for:
+                goto offset,
+                length: u32,
+                revision: u16,
+                certificate_type: u16,
+            );
+
+            if length < 8 || length > end - offset {
+                return Err(ReadImageError::InvalidImage);
+            }
+
+            // Read through `take` so that a bogus length can't allocate more than the file holds
+            let mut buf = vec![];
+            data.take(length as u64 - 8).read_to_end(&mut buf)?;
+            if buf.len() != length as usize - 8 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            certificates.push(Certificate {
+                revision,
+                certificate_type,
+                data: buf,
+            });
+
+            // Entries are aligned to 8 bytes
+            offset = match length
+                .checked_add(7)
+                .and_then(|len| offset.checked_add(len & !7))
+            {
+                Some(next) if next <= end => next,
+                _ => break,
+            };
+        }
+
+        Ok(Self { certificates })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::{DataDirectory, ImageHeader};
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = ImageHeader::read(&mut data).expect("success");
+        let table = super::CertificateTable::read(&header, &mut data).expect("success");
+
+        assert!(table.certificates.is_empty());
+
+        // Append a table with a 5-byte signature padded to 8 bytes, then an X.509 certificate
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let at = bytes.len() as u32;
+        bytes.extend(13u32.to_le_bytes());
+        bytes.extend(super::WIN_CERT_REVISION_2_0.to_le_bytes());
+        bytes.extend(super::WIN_CERT_TYPE_PKCS_SIGNED_DATA.to_le_bytes());
+        bytes.extend(b"pkcs7\0\0\0");
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend(super::WIN_CERT_REVISION_1_0.to_le_bytes());
+        bytes.extend(super::WIN_CERT_TYPE_X509.to_le_bytes());
+        bytes.extend(b"x509cert");
+
+        let mut header = header;
+        header.certificate = DataDirectory { rva: at, size: 32 };
+        let table =
+            super::CertificateTable::read(&header, &mut Cursor::new(&bytes)).expect("success");
+
+        assert_eq!(
+            table.certificates,
+            [
+                super::Certificate {
+                    revision: super::WIN_CERT_REVISION_2_0,
+                    certificate_type: super::WIN_CERT_TYPE_PKCS_SIGNED_DATA,
+                    data: b"pkcs7".to_vec(),
+                },
+                super::Certificate {
+                    revision: super::WIN_CERT_REVISION_1_0,
+                    certificate_type: super::WIN_CERT_TYPE_X509,
+                    data: b"x509cert".to_vec(),
+                },
+            ]
+        );
+
+        // A length past the end of the file fails without allocating it
+        bytes[at as usize..at as usize + 4].copy_from_slice(&0xFFFF_0000u32.to_le_bytes());
+        header.certificate.size = 0xFFFF_0000;
+        let e =
+            super::CertificateTable::read(&header, &mut Cursor::new(&bytes)).expect_err("failure");
+        assert!(matches!(e.root(), crate::error::ReadImageError::IO(_)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::ImageHeader;
+use crate::read;
+use std::io::{Read, Seek};
+
+/// Computes the PE checksum of a whole image, as stored in the optional header's CheckSum field.
+///
+/// The stored checksum itself is excluded from the sum, so this can be compared directly against
+/// `ImageHeader::check_sum`.
+pub fn compute(mut data: &mut (impl Read + Seek)) -> ReadImageResult<u32> {
+    read!(data for:
+        goto 0x3C,
+        pe_signature_offset: u32,
+        goto 0,
+    );
+
+    // Signature, COFF header, then 64 bytes into the optional header
+    let check_sum_offset = pe_signature_offset
+        .checked_add(4 + 20 + 64)
+        .ok_or(ReadImageError::InvalidImage)? as u64;
+
+    let mut sum = 0u64;
+    let mut len = 0u64;
+    let mut buf = [0; 8192];
+    let mut pending: Option<u8> = None;
+
+    loop {
+        let n = data.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        for &b in &buf[..n] {
+            // Treat the checksum field as zero
+            let b = if (check_sum_offset..check_sum_offset + 4).contains(&len) {
+                0
+            } else {
+                b
+            };
+            len += 1;
+
+            match pending.take() {
+                None => pending = Some(b),
+                Some(lo) => {
+                    sum += u16::from_le_bytes([lo, b]) as u64;
+                    sum = (sum & 0xFFFF) + (sum >> 16);
+                }
+            }
+        }
+    }
+
+    if let Some(lo) = pending {
+        sum += lo as u64;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+
+    sum = (sum & 0xFFFF) + (sum >> 16);
+
+    Ok((sum + len) as u32)
+}
+
+/// Returns true if the stored checksum matches the image's contents.
+///
+/// Most images outside of drivers and system DLLs store a checksum of zero, which this reports as
+/// a mismatch.
+pub fn is_valid(header: &ImageHeader, data: &mut (impl Read + Seek)) -> ReadImageResult<bool> {
+    Ok(compute(data)? == header.check_sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+
+        let check_sum = super::compute(&mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(check_sum, 0xFF09);
+
+        // Stamp the computed checksum into the image and make sure it verifies
+        let pe = u32::from_le_bytes(bytes[0x3C..0x40].try_into().unwrap()) as usize;
+        bytes[pe + 88..pe + 92].copy_from_slice(&check_sum.to_le_bytes());
+
+        let mut data = Cursor::new(&bytes);
+        let header = ImageHeader::read(&mut data).expect("success");
+        assert_eq!(header.check_sum, check_sum);
+        assert!(super::is_valid(&header, &mut data).expect("success"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use crate::error::Context;
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::ImageHeader;
+use crate::read;
+use std::io::{Read, Seek};
+
+/// Debug type of a CodeView entry, which points at the PDB file.
+pub const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+/// Debug type emitted by deterministic builds; it carries no data.
+pub const IMAGE_DEBUG_TYPE_REPRO: u32 = 16;
+/// Debug type of a Portable PDB embedded into the image.
+pub const IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB: u32 = 17;
+/// Debug type of a checksum of the associated PDB file.
+pub const IMAGE_DEBUG_TYPE_PDB_CHECKSUM: u32 = 19;
+
+/// The minor version that marks a CodeView entry as pointing to a Portable PDB.
+const PORTABLE_PDB_MINOR_VERSION: u16 = 0x504D;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugDirectory {
+    pub entries: Vec<DebugEntry>,
+}
+
+impl DebugDirectory {
+    pub fn read(header: &ImageHeader, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        let dir = header.debug;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(Self { entries: vec![] });
+        }
+
+        let offset = crate::pe::offset_from(&header.sections, dir.rva)
+            .ok_or(ReadImageError::InvalidImage)?;
+        let count = dir.size / 28;
+
+        let mut raw = Vec::with_capacity(count.min(0x1000) as usize);
+
+        read!(data for: goto offset,);
+
+        for _ in 0..count {
+            read!(data for:
+                characteristics: u32,
+                time_date_stamp: u32,
+                major_version: u16,
+                minor_version: u16,
+                kind: u32,
+                size_of_data: u32,
+                address_of_raw_data: u32,
+                pointer_to_raw_data: u32,
+            );
+            raw.push(DebugEntry {
+                characteristics,
+                time_date_stamp,
+                major_version,
+                minor_version,
+                kind,
+                size_of_data,
+                address_of_raw_data,
+                pointer_to_raw_data,
+                data: DebugData::Unknown,
+            });
+        }
+
+        for (i, entry) in raw.iter_mut().enumerate() {
+            entry.data = DebugData::read(entry, data)
+                .context(entry.pointer_to_raw_data as u64, || {
+                    format!("debug entry {i}")
+                })?;
+        }
+
+        Ok(Self { entries: raw })
+    }
+
+    /// Returns the first CodeView entry, if any.
+    pub fn code_view(&self) -> Option<&CodeView> {
+        self.entries.iter().find_map(|e| match &e.data {
+            DebugData::CodeView(cv) => Some(cv),
+            _ => None,
+        })
+    }
+
+    /// Returns the first PDB checksum entry, if any.
+    pub fn pdb_checksum(&self) -> Option<&PdbChecksum> {
+        self.entries.iter().find_map(|e| match &e.data {
+            DebugData::PdbChecksum(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Returns true if the image was produced by a deterministic build.
+    pub fn is_reproducible(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.kind == IMAGE_DEBUG_TYPE_REPRO)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugEntry {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub kind: u32,
+    pub size_of_data: u32,
+    pub address_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub data: DebugData,
+}
+
+impl DebugEntry {
+    /// Returns true if this is a CodeView entry that refers to a Portable PDB.
+    pub fn is_portable_pdb(&self) -> bool {
+        self.kind == IMAGE_DEBUG_TYPE_CODEVIEW && self.minor_version == PORTABLE_PDB_MINOR_VERSION
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugData {
+    CodeView(CodeView),
+    /// Marks a deterministic build. If present, the COFF timestamp is a content hash, not a date.
+    Reproducible,
+    PdbChecksum(PdbChecksum),
+    /// An entry whose type isn't decoded. Its raw data can be read through `pointer_to_raw_data`.
+    Unknown,
+}
+
+impl DebugData {
+    fn read(entry: &DebugEntry, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        if entry.kind == IMAGE_DEBUG_TYPE_REPRO {
+            return Ok(Self::Reproducible);
+        }
+        if entry.pointer_to_raw_data == 0 || entry.size_of_data == 0 {
+            return Ok(Self::Unknown);
+        }
+        if !matches!(
+            entry.kind,
+            IMAGE_DEBUG_TYPE_CODEVIEW | IMAGE_DEBUG_TYPE_PDB_CHECKSUM
+        ) {
+            return Ok(Self::Unknown);
+        }
+
+        read!(data for: goto entry.pointer_to_raw_data,);
+
+        // Read through `take` so that a bogus size can't allocate more than the file holds
+        let mut buf = vec![];
+        data.take(entry.size_of_data as u64).read_to_end(&mut buf)?;
+        if buf.len() != entry.size_of_data as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        match entry.kind {
+            IMAGE_DEBUG_TYPE_CODEVIEW => Ok(match CodeView::parse(&buf)? {
+                Some(cv) => Self::CodeView(cv),
+                None => Self::Unknown,
+            }),
+            _ => PdbChecksum::parse(&buf).map(Self::PdbChecksum),
+        }
+    }
+}
+
+/// An RSDS CodeView record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeView {
+    pub guid: [u8; 16],
+    pub age: u32,
+    pub path: String,
+}
+
+impl CodeView {
+    /// Returns `None` for other CodeView formats, like the NB10 records of older toolchains.
+    fn parse(buf: &[u8]) -> ReadImageResult<Option<Self>> {
+        if buf.get(..4) != Some(b"RSDS") {
+            return Ok(None);
+        }
+        if buf.len() < 24 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let mut guid = [0; 16];
+        guid.copy_from_slice(&buf[4..20]);
+        let age = u32::from_le_bytes([buf[20], buf[21], buf[22], buf[23]]);
+        let path = null_terminated(&buf[24..])?.to_owned();
+
+        Ok(Some(Self { guid, age, path }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdbChecksum {
+    /// The name of the hash algorithm, like `SHA256`.
+    pub algorithm: String,
+    pub checksum: Vec<u8>,
+}
+
+impl PdbChecksum {
+    fn parse(buf: &[u8]) -> ReadImageResult<Self> {
+        let algorithm = null_terminated(buf)?;
+        let checksum = buf[algorithm.len() + 1..].to_vec();
+
+        Ok(Self {
+            algorithm: algorithm.to_owned(),
+            checksum,
+        })
+    }
+}
+
+fn null_terminated(buf: &[u8]) -> ReadImageResult<&str> {
+    let len = buf
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(ReadImageError::InvalidImage)?;
+    Ok(std::str::from_utf8(&buf[..len])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = ImageHeader::read(&mut data).expect("success");
+        let debug = super::DebugDirectory::read(&header, &mut data).expect("success");
+
+        let cv = debug.code_view().expect("codeview entry");
+        assert!(cv.path.ends_with("HelloWorld.pdb"));
+        assert_eq!(cv.age, 1);
+        assert_eq!(
+            debug.pdb_checksum().expect("checksum entry").algorithm,
+            "SHA256"
+        );
+        assert!(debug.is_reproducible());
+        assert!(debug.entries[0].is_portable_pdb());
+
+        // Point the CodeView data past the end of the file
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let dir =
+            crate::pe::offset_from(&header.sections, header.debug.rva).expect("offset") as usize;
+        bytes[dir + 24..dir + 28].copy_from_slice(&0xFFFF_0000u32.to_le_bytes());
+
+        let e = super::DebugDirectory::read(&header, &mut Cursor::new(bytes)).expect_err("failure");
+        assert_eq!(e.path(), ["debug entry 0"]);
+        assert_eq!(e.offset(), Some(0xFFFF_0000));
+        assert!(matches!(e.root(), crate::error::ReadImageError::IO(_)));
+
+        // An NB10 CodeView record is left undecoded instead of failing the directory
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let cv = debug.entries[0].pointer_to_raw_data as usize;
+        bytes[cv..cv + 4].copy_from_slice(b"NB10");
+        let debug =
+            super::DebugDirectory::read(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert!(debug.code_view().is_none());
+        assert_eq!(debug.entries[0].data, super::DebugData::Unknown);
+
+        // Entries that aren't decoded aren't read, whatever their size
+        bytes[dir + 12..dir + 16]
+            .copy_from_slice(&super::IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB.to_le_bytes());
+        bytes[dir + 16..dir + 20].copy_from_slice(&u32::MAX.to_le_bytes());
+        let debug =
+            super::DebugDirectory::read(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(debug.entries[0].data, super::DebugData::Unknown);
+
+        // A CodeView size larger than the file fails without allocating it
+        bytes[dir + 12..dir + 16].copy_from_slice(&super::IMAGE_DEBUG_TYPE_CODEVIEW.to_le_bytes());
+        let e =
+            super::DebugDirectory::read(&header, &mut Cursor::new(&bytes)).expect_err("failure");
+        assert!(matches!(e.root(), crate::error::ReadImageError::IO(_)));
+
+        Ok(())
+    }
+}
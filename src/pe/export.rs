@@ -0,0 +1,223 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::{offset_from, read_c_str, ImageHeader};
+use crate::read;
+use std::io::{Read, Seek};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportDirectory {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    /// The name of the DLL, as recorded by the linker.
+    pub name: String,
+    pub ordinal_base: u32,
+    pub exports: Vec<Export>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub ordinal: u32,
+    /// `None` if the function is only exported by ordinal.
+    pub name: Option<String>,
+    pub target: ExportTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    Rva(u32),
+    /// The export is forwarded to another DLL, like `NTDLL.RtlAllocateHeap` or `NTDLL.#12`.
+    Forwarder(String),
+}
+
+impl ExportDirectory {
+    /// Reads the export directory. Returns `None` if the image exports nothing.
+    pub fn read(
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let dir = header.export;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(None);
+        }
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for:
+            goto offset,
+            characteristics: u32,
+            time_date_stamp: u32,
+            major_version: u16,
+            minor_version: u16,
+            name: u32,
+            ordinal_base: u32,
+            number_of_functions: u32,
+            number_of_names: u32,
+            address_of_functions: u32,
+            address_of_names: u32,
+            address_of_name_ordinals: u32,
+        );
+
+        let functions = read_array::<u32>(header, address_of_functions, number_of_functions, data)?;
+        let names = read_array::<u32>(header, address_of_names, number_of_names, data)?;
+        let name_ordinals =
+            read_array::<u16>(header, address_of_name_ordinals, number_of_names, data)?;
+
+        let mut function_names = vec![None; functions.len()];
+        for (&name, &index) in names.iter().zip(&name_ordinals) {
+            let slot = function_names
+                .get_mut(index as usize)
+                .ok_or(ReadImageError::InvalidImage)?;
+            let name = offset_from(&header.sections, name).ok_or(ReadImageError::InvalidImage)?;
+            *slot = Some(read_c_str(name, data)?);
+        }
+
+        let mut exports = Vec::with_capacity(functions.len());
+        for (i, (rva, name)) in functions.into_iter().zip(function_names).enumerate() {
+            // Unused slots in the address table are zero
+            if rva == 0 {
+                continue;
+            }
+
+            let target = if rva >= dir.rva && rva - dir.rva < dir.size {
+                let forwarder =
+                    offset_from(&header.sections, rva).ok_or(ReadImageError::InvalidImage)?;
+                ExportTarget::Forwarder(read_c_str(forwarder, data)?)
+            } else {
+                ExportTarget::Rva(rva)
+            };
+
+            exports.push(Export {
+                ordinal: ordinal_base.wrapping_add(i as u32),
+                name,
+                target,
+            });
+        }
+
+        let name = offset_from(&header.sections, name).ok_or(ReadImageError::InvalidImage)?;
+        let name = read_c_str(name, data)?;
+
+        Ok(Some(Self {
+            characteristics,
+            time_date_stamp,
+            major_version,
+            minor_version,
+            name,
+            ordinal_base,
+            exports,
+        }))
+    }
+}
+
+trait ArrayElement: Sized {
+    fn read(data: &mut impl Read) -> std::io::Result<Self>;
+}
+
+impl ArrayElement for u16 {
+    fn read(mut data: &mut impl Read) -> std::io::Result<Self> {
+        Ok(read! { data u16 })
+    }
+}
+
+impl ArrayElement for u32 {
+    fn read(mut data: &mut impl Read) -> std::io::Result<Self> {
+        Ok(read! { data u32 })
+    }
+}
+
+fn read_array<T: ArrayElement>(
+    header: &ImageHeader,
+    rva: u32,
+    count: u32,
+    mut data: &mut (impl Read + Seek),
+) -> ReadImageResult<Vec<T>> {
+    if count == 0 {
+        return Ok(vec![]);
+    }
+
+    let offset = offset_from(&header.sections, rva).ok_or(ReadImageError::InvalidImage)?;
+
+    read!(data for: goto offset,);
+
+    // Don't trust the count for the allocation; a bogus one fails at EOF instead
+    let mut items = Vec::with_capacity(count.min(0x1000) as usize);
+    for _ in 0..count {
+        items.push(T::read(data)?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::{DataDirectory, ImageHeader};
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let mut header = ImageHeader::read(&mut data).expect("success");
+        let exports = super::ExportDirectory::read(&header, &mut data).expect("success");
+
+        assert_eq!(exports, None);
+
+        // Fake a directory in the slack space at the end of .text, at RVA 0x26C0, exporting a
+        // named function, an ordinal-only function and a named forwarder
+        header.sections[0].virtual_size = 0x800;
+        header.export = DataDirectory {
+            rva: 0x26C0,
+            size: 0x90,
+        };
+
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let mut put = |rva: usize, value: &[u8]| {
+            bytes[rva - 0x1E00..rva - 0x1E00 + value.len()].copy_from_slice(value);
+        };
+        let dir: Vec<u8> = [0u32, 0, 0, 0x2740, 5, 3, 2, 0x26E8, 0x26F4, 0x26FC]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        put(0x26C0, &dir);
+        put(
+            0x26E8,
+            &[0x00, 0x10, 0, 0, 0x10, 0x10, 0, 0, 0x10, 0x27, 0, 0],
+        );
+        put(0x26F4, &[0x30, 0x27, 0, 0, 0x38, 0x27, 0, 0]);
+        put(0x26FC, &[0, 0, 2, 0]);
+        put(0x2710, b"NTDLL.RtlAllocateHeap\0");
+        put(0x2730, b"Run\0");
+        put(0x2738, b"Alloc\0");
+        put(0x2740, b"test.dll\0");
+
+        let exports = super::ExportDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("export directory");
+
+        assert_eq!(exports.name, "test.dll");
+        assert_eq!(
+            exports.exports,
+            [
+                super::Export {
+                    ordinal: 5,
+                    name: Some("Run".into()),
+                    target: super::ExportTarget::Rva(0x1000),
+                },
+                super::Export {
+                    ordinal: 6,
+                    name: None,
+                    target: super::ExportTarget::Rva(0x1010),
+                },
+                super::Export {
+                    ordinal: 7,
+                    name: Some("Alloc".into()),
+                    target: super::ExportTarget::Forwarder("NTDLL.RtlAllocateHeap".into()),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::Context;
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::{offset_from, read_c_str, ImageHeader};
+use crate::read;
+use std::io::{Read, Seek};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportDirectory {
+    pub modules: Vec<ImportedModule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedModule {
+    pub name: String,
+    pub time_date_stamp: u32,
+    pub forwarder_chain: u32,
+    pub import_lookup_table: u32,
+    pub import_address_table: u32,
+    pub functions: Vec<ImportedFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedFunction {
+    /// The RVA of this function's IAT slot, which the loader overwrites with its address.
+    pub thunk_rva: u32,
+    pub import: Import,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Import {
+    Ordinal(u16),
+    Name { hint: u16, name: String },
+}
+
+impl ImportDirectory {
+    pub fn read(header: &ImageHeader, mut data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        let dir = header.import;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(Self { modules: vec![] });
+        }
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        let mut descriptors = vec![];
+
+        read!(data for: goto offset,);
+
+        loop {
+            read!(data for:
+                import_lookup_table: u32,
+                time_date_stamp: u32,
+                forwarder_chain: u32,
+                name: u32,
+                import_address_table: u32,
+            );
+            if import_lookup_table == 0 && name == 0 && import_address_table == 0 {
+                break;
+            }
+            descriptors.push((
+                import_lookup_table,
+                time_date_stamp,
+                forwarder_chain,
+                name,
+                import_address_table,
+            ));
+        }
+
+        let mut modules = Vec::with_capacity(descriptors.len());
+
+        for (
+            i,
+            (import_lookup_table, time_date_stamp, forwarder_chain, name, import_address_table),
+        ) in descriptors.into_iter().enumerate()
+        {
+            let descriptor_offset = offset as u64 + i as u64 * 20;
+
+            let name_offset =
+                offset_from(&header.sections, name).ok_or(ReadImageError::InvalidImage)?;
+            let name = read_c_str(name_offset, data)
+                .context(descriptor_offset, || format!("import descriptor {i} name"))?;
+
+            // Bound images overwrite the IAT on disk, so prefer the lookup table when there is one
+            let table = match import_lookup_table {
+                0 => import_address_table,
+                rva => rva,
+            };
+            let thunks = read_thunks(header, table, data).context(descriptor_offset, || {
+                format!("import descriptor {i} ({name}) thunks")
+            })?;
+
+            let mut functions = Vec::with_capacity(thunks.len());
+            for (i, thunk) in thunks.into_iter().enumerate() {
+                let import = if thunk & ordinal_flag(header) != 0 {
+                    Import::Ordinal(thunk as u16)
+                } else {
+                    let hint_name = offset_from(&header.sections, thunk as u32)
+                        .ok_or(ReadImageError::InvalidImage)?;
+                    read!(data for:
+                        goto hint_name,
+                        hint: u16,
+                    );
+                    let name = read_c_str(
+                        hint_name.checked_add(2).ok_or(ReadImageError::Overflow)?,
+                        data,
+                    )?;
+                    Import::Name { hint, name }
+                };
+
+                functions.push(ImportedFunction {
+                    thunk_rva: (i as u32)
+                        .checked_mul(thunk_size(header))
+                        .and_then(|delta| import_address_table.checked_add(delta))
+                        .ok_or(ReadImageError::Overflow)?,
+                    import,
+                });
+            }
+
+            modules.push(ImportedModule {
+                name,
+                time_date_stamp,
+                forwarder_chain,
+                import_lookup_table,
+                import_address_table,
+                functions,
+            });
+        }
+
+        Ok(Self { modules })
+    }
+}
+
+fn thunk_size(header: &ImageHeader) -> u32 {
+    if header.pe64 {
+        8
+    } else {
+        4
+    }
+}
+
+fn ordinal_flag(header: &ImageHeader) -> u64 {
+    if header.pe64 {
+        1 << 63
+    } else {
+        1 << 31
+    }
+}
+
+fn read_thunks(
+    header: &ImageHeader,
+    rva: u32,
+    mut data: &mut (impl Read + Seek),
+) -> ReadImageResult<Vec<u64>> {
+    let offset = offset_from(&header.sections, rva).ok_or(ReadImageError::InvalidImage)?;
+
+    read!(data for: goto offset,);
+
+    let mut thunks = vec![];
+    loop {
+        let thunk = match header.pe64 {
+            true => read! { data u64 },
+            false => (read! { data u32 }) as u64,
+        };
+        if thunk == 0 {
+            break;
+        }
+        thunks.push(thunk);
+    }
+
+    Ok(thunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = ImageHeader::read(&mut data).expect("success");
+        let imports = super::ImportDirectory::read(&header, &mut data).expect("success");
+
+        assert_eq!(imports.modules.len(), 1);
+        assert_eq!(imports.modules[0].name, "mscoree.dll");
+        assert_eq!(
+            imports.modules[0].functions[0].import,
+            super::Import::Name {
+                hint: 0,
+                name: "_CorExeMain".into()
+            }
+        );
+
+        Ok(())
+    }
+}
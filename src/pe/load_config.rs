@@ -0,0 +1,245 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::{offset_from, ImageHeader};
+use crate::read;
+use std::io::{Cursor, Read, Seek};
+
+/// Set in `guard_flags` when the image was compiled with Control Flow Guard.
+pub const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x100;
+/// Set in `guard_flags` when the image has a valid CFG function table.
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT: u32 = 0x400;
+
+/// The load configuration directory, up to and including `guard_flags`.
+///
+/// The structure has grown over time; fields past the recorded `size` are zero. Pointer-sized
+/// fields are VAs and are widened to `u64` for PE32 images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadConfigDirectory {
+    pub size: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub global_flags_clear: u32,
+    pub global_flags_set: u32,
+    pub critical_section_default_timeout: u32,
+    pub de_commit_free_block_threshold: u64,
+    pub de_commit_total_free_threshold: u64,
+    pub lock_prefix_table: u64,
+    pub maximum_allocation_size: u64,
+    pub virtual_memory_threshold: u64,
+    pub process_affinity_mask: u64,
+    pub process_heap_flags: u32,
+    pub csd_version: u16,
+    pub dependent_load_flags: u16,
+    pub edit_list: u64,
+    pub security_cookie: u64,
+    pub se_handler_table: u64,
+    pub se_handler_count: u64,
+    pub guard_cf_check_function_pointer: u64,
+    pub guard_cf_dispatch_function_pointer: u64,
+    pub guard_cf_function_table: u64,
+    pub guard_cf_function_count: u64,
+    pub guard_flags: u32,
+}
+
+impl LoadConfigDirectory {
+    /// Reads the load config directory. Returns `None` if the image has none.
+    pub fn read(
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let dir = header.load_config;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(None);
+        }
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for:
+            goto offset,
+            size: u32,
+        );
+
+        if size < 4 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        // Copy what the image has into a buffer as large as the newest layout we understand
+        let mut buf = vec![0; if header.pe64 { 148 } else { 92 }];
+        let len = (size as usize).min(buf.len());
+        buf[..4].copy_from_slice(&size.to_le_bytes());
+        data.read_exact(&mut buf[4..len])?;
+
+        let pe64 = header.pe64;
+        let mut buf = Cursor::new(buf);
+        let ptr = |mut buf: &mut Cursor<Vec<u8>>| -> ReadImageResult<u64> {
+            Ok(match pe64 {
+                true => read! { buf u64 },
+                false => (read! { buf u32 }) as u64,
+            })
+        };
+
+        read!(buf for:
+            skip 4,
+            time_date_stamp: u32,
+            major_version: u16,
+            minor_version: u16,
+            global_flags_clear: u32,
+            global_flags_set: u32,
+            critical_section_default_timeout: u32,
+        );
+
+        let de_commit_free_block_threshold = ptr(&mut buf)?;
+        let de_commit_total_free_threshold = ptr(&mut buf)?;
+        let lock_prefix_table = ptr(&mut buf)?;
+        let maximum_allocation_size = ptr(&mut buf)?;
+        let virtual_memory_threshold = ptr(&mut buf)?;
+
+        // PE32 and PE32+ disagree on the order of these two
+        let (process_affinity_mask, process_heap_flags) = if pe64 {
+            let mask = ptr(&mut buf)?;
+            (mask, read! { buf u32 })
+        } else {
+            let flags = read! { buf u32 };
+            (ptr(&mut buf)?, flags)
+        };
+
+        read!(buf for:
+            csd_version: u16,
+            dependent_load_flags: u16,
+        );
+
+        let edit_list = ptr(&mut buf)?;
+        let security_cookie = ptr(&mut buf)?;
+        let se_handler_table = ptr(&mut buf)?;
+        let se_handler_count = ptr(&mut buf)?;
+        let guard_cf_check_function_pointer = ptr(&mut buf)?;
+        let guard_cf_dispatch_function_pointer = ptr(&mut buf)?;
+        let guard_cf_function_table = ptr(&mut buf)?;
+        let guard_cf_function_count = ptr(&mut buf)?;
+        let guard_flags = read! { buf u32 };
+
+        Ok(Some(Self {
+            size,
+            time_date_stamp,
+            major_version,
+            minor_version,
+            global_flags_clear,
+            global_flags_set,
+            critical_section_default_timeout,
+            de_commit_free_block_threshold,
+            de_commit_total_free_threshold,
+            lock_prefix_table,
+            maximum_allocation_size,
+            virtual_memory_threshold,
+            process_affinity_mask,
+            process_heap_flags,
+            csd_version,
+            dependent_load_flags,
+            edit_list,
+            security_cookie,
+            se_handler_table,
+            se_handler_count,
+            guard_cf_check_function_pointer,
+            guard_cf_dispatch_function_pointer,
+            guard_cf_function_table,
+            guard_cf_function_count,
+            guard_flags,
+        }))
+    }
+
+    /// Returns true if the image was compiled with Control Flow Guard.
+    pub fn has_cfg(&self) -> bool {
+        self.guard_flags & IMAGE_GUARD_CF_INSTRUMENTED != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::{DataDirectory, ImageHeader};
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let mut header = ImageHeader::read(&mut data).expect("success");
+
+        assert_eq!(
+            super::LoadConfigDirectory::read(&header, &mut data).expect("success"),
+            None
+        );
+
+        // Fake directories in the slack space at the end of .text, at RVA 0x26C0
+        header.sections[0].virtual_size = 0x800;
+        header.load_config = DataDirectory {
+            rva: 0x26C0,
+            size: 0x40,
+        };
+
+        let put = |bytes: &mut Vec<u8>, at: usize, value: &[u8]| {
+            bytes[0x8C0 + at..0x8C0 + at + value.len()].copy_from_slice(value);
+        };
+
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        put(&mut bytes, 0, &92u32.to_le_bytes());
+        put(&mut bytes, 8, &[1, 0, 2, 0]);
+        put(&mut bytes, 44, &0x4u32.to_le_bytes());
+        put(&mut bytes, 48, &0xFu32.to_le_bytes());
+        put(&mut bytes, 52, &[3, 0, 0x00, 0x08]);
+        put(&mut bytes, 60, &0x403000u32.to_le_bytes());
+        put(&mut bytes, 84, &7u32.to_le_bytes());
+        put(&mut bytes, 88, &0x500u32.to_le_bytes());
+
+        let config = super::LoadConfigDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("load config");
+        assert_eq!((config.major_version, config.minor_version), (1, 2));
+        assert_eq!(config.process_heap_flags, 0x4);
+        assert_eq!(config.process_affinity_mask, 0xF);
+        assert_eq!(config.csd_version, 3);
+        assert_eq!(config.dependent_load_flags, 0x800);
+        assert_eq!(config.security_cookie, 0x403000);
+        assert_eq!(config.guard_cf_function_count, 7);
+        assert_eq!(config.guard_flags, 0x500);
+        assert!(config.has_cfg());
+
+        // An older, shorter structure leaves the newer fields zeroed
+        put(&mut bytes, 0, &64u32.to_le_bytes());
+        let config = super::LoadConfigDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("load config");
+        assert_eq!(config.security_cookie, 0x403000);
+        assert_eq!(config.guard_flags, 0);
+
+        // PE32+ widens the pointers and swaps the affinity mask and heap flags
+        header.pe64 = true;
+        header.load_config.size = 148;
+
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        put(&mut bytes, 0, &148u32.to_le_bytes());
+        put(&mut bytes, 24, &0x1_0000_0001u64.to_le_bytes());
+        put(&mut bytes, 64, &0xFFu64.to_le_bytes());
+        put(&mut bytes, 72, &0x4u32.to_le_bytes());
+        put(&mut bytes, 76, &[3, 0, 0x00, 0x08]);
+        put(&mut bytes, 88, &0x1_8000_3000u64.to_le_bytes());
+        put(&mut bytes, 136, &9u64.to_le_bytes());
+        put(&mut bytes, 144, &0x100u32.to_le_bytes());
+
+        let config = super::LoadConfigDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("load config");
+        assert_eq!(config.de_commit_free_block_threshold, 0x1_0000_0001);
+        assert_eq!(config.process_affinity_mask, 0xFF);
+        assert_eq!(config.process_heap_flags, 0x4);
+        assert_eq!(config.csd_version, 3);
+        assert_eq!(config.dependent_load_flags, 0x800);
+        assert_eq!(config.security_cookie, 0x1_8000_3000);
+        assert_eq!(config.guard_cf_function_count, 9);
+        assert_eq!(config.guard_flags, 0x100);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+use crate::error::Context;
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::ImageHeader;
+use crate::read;
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+
+pub const RT_CURSOR: u32 = 1;
+pub const RT_BITMAP: u32 = 2;
+pub const RT_ICON: u32 = 3;
+pub const RT_MENU: u32 = 4;
+pub const RT_DIALOG: u32 = 5;
+pub const RT_STRING: u32 = 6;
+pub const RT_RCDATA: u32 = 10;
+pub const RT_GROUP_CURSOR: u32 = 12;
+pub const RT_GROUP_ICON: u32 = 14;
+pub const RT_VERSION: u32 = 16;
+pub const RT_MANIFEST: u32 = 24;
+
+/// Resource trees are normally three levels deep (type, name, language). Anything
+/// much deeper than that is almost certainly a cycle.
+const MAX_DEPTH: u32 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceDirectory {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub entries: Vec<ResourceEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceEntry {
+    pub name: ResourceName,
+    pub node: ResourceNode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceName {
+    Id(u32),
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceNode {
+    Directory(ResourceDirectory),
+    Data(ResourceData),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceData {
+    pub rva: u32,
+    pub size: u32,
+    pub code_page: u32,
+}
+
+impl ResourceDirectory {
+    /// Reads the whole resource tree. Returns an empty directory if the image has no resources.
+    pub fn read(header: &ImageHeader, data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        let dir = header.resource;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(Self {
+                characteristics: 0,
+                time_date_stamp: 0,
+                major_version: 0,
+                minor_version: 0,
+                entries: vec![],
+            });
+        }
+
+        let base = crate::pe::offset_from(&header.sections, dir.rva)
+            .ok_or(ReadImageError::InvalidImage)?;
+
+        Self::read_at(base, 0, 0, &mut HashSet::new(), data)
+    }
+
+    /// `visited` holds the offsets of every directory read so far. Directories are never shared
+    /// in a real tree, and a shared one would otherwise be read again for every path to it.
+    fn read_at(
+        base: u32,
+        offset: u32,
+        depth: u32,
+        visited: &mut HashSet<u32>,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Self> {
+        if depth > MAX_DEPTH || !visited.insert(offset) {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        read!(data for:
+            goto add(base, offset)?,
+            characteristics: u32,
+            time_date_stamp: u32,
+            major_version: u16,
+            minor_version: u16,
+            number_of_named_entries: u16,
+            number_of_id_entries: u16,
+        );
+
+        let count = number_of_named_entries as u32 + number_of_id_entries as u32;
+        let mut raw = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            read!(data for:
+                name: u32,
+                target: u32,
+            );
+            raw.push((name, target));
+        }
+
+        let mut entries = Vec::with_capacity(raw.len());
+
+        for (name, target) in raw {
+            let name = if name & 0x8000_0000 != 0 {
+                ResourceName::Name(read_name(add(base, name & 0x7FFF_FFFF)?, data)?)
+            } else {
+                ResourceName::Id(name)
+            };
+
+            let node = if target & 0x8000_0000 != 0 {
+                let offset = target & 0x7FFF_FFFF;
+                ResourceNode::Directory(
+                    Self::read_at(base, offset, depth + 1, visited, data)
+                        .context(add(base, offset)? as u64, || {
+                            format!("resource directory {name:?}")
+                        })?,
+                )
+            } else {
+                read!(data for:
+                    goto add(base, target)?,
+                    rva: u32,
+                    size: u32,
+                    code_page: u32,
+                );
+                ResourceNode::Data(ResourceData {
+                    rva,
+                    size,
+                    code_page,
+                })
+            };
+
+            entries.push(ResourceEntry { name, node });
+        }
+
+        Ok(Self {
+            characteristics,
+            time_date_stamp,
+            major_version,
+            minor_version,
+            entries,
+        })
+    }
+
+    /// Returns the entry with the given name in this directory.
+    pub fn get(&self, name: &ResourceName) -> Option<&ResourceNode> {
+        self.entries
+            .iter()
+            .find(|e| &e.name == name)
+            .map(|e| &e.node)
+    }
+
+    /// Returns the subdirectory with the given integer id.
+    pub fn subdirectory(&self, id: u32) -> Option<&ResourceDirectory> {
+        match self.get(&ResourceName::Id(id)) {
+            Some(ResourceNode::Directory(dir)) => Some(dir),
+            _ => None,
+        }
+    }
+
+    /// Iterates over every data leaf beneath this directory, depth-first.
+    pub fn leaves(&self) -> impl Iterator<Item = &ResourceData> {
+        let mut stack = vec![self.entries.iter()];
+
+        std::iter::from_fn(move || {
+            while let Some(iter) = stack.last_mut() {
+                match iter.next() {
+                    Some(ResourceEntry {
+                        node: ResourceNode::Data(leaf),
+                        ..
+                    }) => return Some(leaf),
+                    Some(ResourceEntry {
+                        node: ResourceNode::Directory(dir),
+                        ..
+                    }) => stack.push(dir.entries.iter()),
+                    None => {
+                        stack.pop();
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Reads the first RT_MANIFEST resource as text.
+    pub fn manifest(
+        &self,
+        header: &ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<String>> {
+        let leaf = match self
+            .subdirectory(RT_MANIFEST)
+            .and_then(|d| d.leaves().next())
+        {
+            Some(leaf) => leaf,
+            None => return Ok(None),
+        };
+
+        let bytes = leaf.read_bytes(header, data)?;
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+
+        Ok(Some(std::str::from_utf8(bytes)?.to_owned()))
+    }
+
+    /// Reassembles every RT_GROUP_ICON resource and the RT_ICON images it refers to into the
+    /// bytes of an `.ico` file.
+    pub fn icons(
+        &self,
+        header: &ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<Vec<u8>>> {
+        let (groups, images) = match (self.subdirectory(RT_GROUP_ICON), self.subdirectory(RT_ICON))
+        {
+            (Some(groups), Some(images)) => (groups, images),
+            _ => return Ok(vec![]),
+        };
+
+        let mut icons = vec![];
+
+        for group in groups.leaves() {
+            let group = group.read_bytes(header, data)?;
+            if group.len() < 6 {
+                return Err(ReadImageError::InvalidImage);
+            }
+
+            let count = u16::from_le_bytes([group[4], group[5]]) as usize;
+            if group.len() < 6 + count * 14 {
+                return Err(ReadImageError::InvalidImage);
+            }
+
+            let mut entries = Vec::with_capacity(count);
+            let mut bitmaps = Vec::with_capacity(count);
+
+            for entry in group[6..].chunks_exact(14).take(count) {
+                let id = u16::from_le_bytes([entry[12], entry[13]]) as u32;
+                let leaf = match images.get(&ResourceName::Id(id)) {
+                    Some(ResourceNode::Directory(dir)) => dir.leaves().next(),
+                    Some(ResourceNode::Data(leaf)) => Some(leaf),
+                    None => None,
+                };
+                let leaf = leaf.ok_or(ReadImageError::InvalidImage)?;
+
+                entries.push(&entry[..12]);
+                bitmaps.push(leaf.read_bytes(header, data)?);
+            }
+
+            // ICONDIR, then one ICONDIRENTRY per image, then the images themselves
+            let mut ico = group[..6].to_vec();
+            let mut offset = 6 + count as u32 * 16;
+
+            for (entry, bitmap) in entries.iter().zip(&bitmaps) {
+                let len = u32::try_from(bitmap.len()).map_err(|_| ReadImageError::Overflow)?;
+                ico.extend_from_slice(&entry[..8]);
+                ico.extend_from_slice(&len.to_le_bytes());
+                ico.extend_from_slice(&offset.to_le_bytes());
+                offset = add(offset, len)?;
+            }
+            for bitmap in bitmaps {
+                ico.extend(bitmap);
+            }
+
+            icons.push(ico);
+        }
+
+        Ok(icons)
+    }
+}
+
+impl ResourceData {
+    pub fn read_bytes(
+        &self,
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<u8>> {
+        let offset = crate::pe::offset_range_from(&header.sections, self.rva, self.size)
+            .ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        // Read through `take` so that a bogus size can't allocate more than the file holds
+        let mut buf = vec![];
+        data.take(self.size as u64).read_to_end(&mut buf)?;
+        if buf.len() != self.size as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(buf)
+    }
+}
+
+fn read_name(offset: u32, mut data: &mut (impl Read + Seek)) -> ReadImageResult<String> {
+    read!(data for:
+        goto offset,
+        len: u16,
+    );
+
+    let mut units = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        units.push(read! { data u16 });
+    }
+
+    String::from_utf16(&units).map_err(|_| ReadImageError::InvalidImage)
+}
+
+fn add(base: u32, offset: u32) -> ReadImageResult<u32> {
+    base.checked_add(offset).ok_or(ReadImageError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let mut header = ImageHeader::read(&mut data).expect("success");
+        let rsrc = super::ResourceDirectory::read(&header, &mut data).expect("success");
+
+        assert!(rsrc.subdirectory(super::RT_VERSION).is_some());
+        assert!(rsrc.subdirectory(super::RT_MANIFEST).is_some());
+        assert_eq!(rsrc.leaves().count(), 2);
+
+        let manifest = rsrc.manifest(&header, &mut data).expect("success");
+        assert!(manifest.expect("manifest").contains("<assembly"));
+        assert!(rsrc.icons(&header, &mut data).expect("success").is_empty());
+
+        // An icon group with two images in the slack space at the end of .text, at RVA 0x26C0
+        header.sections[0].virtual_size = 0x800;
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let mut group = vec![0, 0, 1, 0, 2, 0];
+        group.extend([16, 16, 0, 0, 1, 0, 32, 0, 8, 0, 0, 0, 1, 0]);
+        group.extend([32, 32, 0, 0, 1, 0, 32, 0, 4, 0, 0, 0, 2, 0]);
+        bytes[0x8C0..0x8C0 + group.len()].copy_from_slice(&group);
+        bytes[0x900..0x908].copy_from_slice(b"image 1!");
+        bytes[0x910..0x914].copy_from_slice(b"img2");
+
+        let leaf = |rva, size| {
+            super::ResourceNode::Data(super::ResourceData {
+                rva,
+                size,
+                code_page: 0,
+            })
+        };
+        let dir = |entries: Vec<(u32, super::ResourceNode)>| super::ResourceDirectory {
+            characteristics: 0,
+            time_date_stamp: 0,
+            major_version: 0,
+            minor_version: 0,
+            entries: entries
+                .into_iter()
+                .map(|(id, node)| super::ResourceEntry {
+                    name: super::ResourceName::Id(id),
+                    node,
+                })
+                .collect(),
+        };
+        let rsrc = dir(vec![
+            (
+                super::RT_ICON,
+                super::ResourceNode::Directory(dir(vec![
+                    (1, leaf(0x2700, 8)),
+                    (
+                        2,
+                        super::ResourceNode::Directory(dir(vec![(0x409, leaf(0x2710, 4))])),
+                    ),
+                ])),
+            ),
+            (
+                super::RT_GROUP_ICON,
+                super::ResourceNode::Directory(dir(vec![(
+                    1,
+                    super::ResourceNode::Directory(dir(vec![(
+                        0x409,
+                        leaf(0x26C0, group.len() as u32),
+                    )])),
+                )])),
+            ),
+        ]);
+
+        let icons = rsrc
+            .icons(&header, &mut Cursor::new(&bytes))
+            .expect("success");
+        let mut ico = vec![0, 0, 1, 0, 2, 0];
+        ico.extend([16, 16, 0, 0, 1, 0, 32, 0, 8, 0, 0, 0, 38, 0, 0, 0]);
+        ico.extend([32, 32, 0, 0, 1, 0, 32, 0, 4, 0, 0, 0, 46, 0, 0, 0]);
+        ico.extend(b"image 1!img2");
+        assert_eq!(icons, [ico]);
+
+        // Data running past the end of its section fails before anything is allocated
+        let huge = super::ResourceData {
+            rva: 0x26C0,
+            size: u32::MAX,
+            code_page: 0,
+        };
+        assert!(matches!(
+            huge.read_bytes(&header, &mut Cursor::new(&bytes)),
+            Err(crate::error::ReadImageError::InvalidImage)
+        ));
+
+        // A root whose two entries share one subdirectory, which would expand exponentially
+        // if each level did the same
+        let mut rsrc = vec![0; 16];
+        rsrc[14] = 2;
+        for id in [1u32, 2] {
+            rsrc.extend(id.to_le_bytes());
+            rsrc.extend(0x8000_0020u32.to_le_bytes());
+        }
+        rsrc.extend([0; 16]);
+        bytes[0x8C0..0x8C0 + rsrc.len()].copy_from_slice(&rsrc);
+        header.resource = crate::pe::DataDirectory {
+            rva: 0x26C0,
+            size: rsrc.len() as u32,
+        };
+
+        let e =
+            super::ResourceDirectory::read(&header, &mut Cursor::new(&bytes)).expect_err("failure");
+        assert!(matches!(
+            e.root(),
+            crate::error::ReadImageError::InvalidImage
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::debug::{DebugDirectory, IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB};
+use crate::pe::{offset_from, DataDirectory, ImageHeader};
+use std::io::Cursor;
+
+const RESOURCE_INDEX: u32 = 2;
+const DEBUG_INDEX: u32 = 6;
+
+/// Removes the debug directory from an image in place, zeroing every entry and its data, like
+/// CodeView paths and embedded PDBs. The layout isn't changed, so no other offsets move.
+pub fn strip_debug(bytes: &mut [u8]) -> ReadImageResult<()> {
+    let header = ImageHeader::read(&mut Cursor::new(&*bytes))?;
+    let debug = DebugDirectory::read(&header, &mut Cursor::new(&*bytes))?;
+
+    for entry in &debug.entries {
+        zero_data(bytes, entry.pointer_to_raw_data, entry.size_of_data);
+    }
+
+    clear_directory(bytes, &header, DEBUG_INDEX, header.debug)
+}
+
+/// Zeroes the data of embedded Portable PDB debug entries in place, leaving the other entries.
+/// The entries stay in the directory but with no data, which readers treat as absent.
+pub fn strip_embedded_pdb(bytes: &mut [u8]) -> ReadImageResult<()> {
+    let header = ImageHeader::read(&mut Cursor::new(&*bytes))?;
+    if header.debug.rva == 0 || header.debug.size == 0 {
+        return Ok(());
+    }
+
+    let debug = DebugDirectory::read(&header, &mut Cursor::new(&*bytes))?;
+    let dir_offset =
+        offset_from(&header.sections, header.debug.rva).ok_or(ReadImageError::InvalidImage)?;
+
+    for (i, entry) in debug.entries.iter().enumerate() {
+        if entry.kind == IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB {
+            zero_data(bytes, entry.pointer_to_raw_data, entry.size_of_data);
+            // SizeOfData, AddressOfRawData and PointerToRawData
+            zero(bytes, dir_offset as u64 + i as u64 * 28 + 16, 12)?;
+        }
+    }
+
+    update_check_sum(bytes, &header)
+}
+
+/// Removes the Win32 resources from an image in place. The `.rsrc` section itself stays, but its
+/// contents are zeroed.
+pub fn strip_resources(bytes: &mut [u8]) -> ReadImageResult<()> {
+    let header = ImageHeader::read(&mut Cursor::new(&*bytes))?;
+    clear_directory(bytes, &header, RESOURCE_INDEX, header.resource)
+}
+
+/// Zeroes a data directory's contents and its entry in the optional header.
+fn clear_directory(
+    bytes: &mut [u8],
+    header: &ImageHeader,
+    index: u32,
+    dir: DataDirectory,
+) -> ReadImageResult<()> {
+    if dir.rva == 0 || dir.size == 0 {
+        return Ok(());
+    }
+
+    let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+    zero(bytes, offset as u64, dir.size)?;
+
+    let data_directories = match header.pe64 {
+        true => 112,
+        false => 96,
+    };
+    zero(
+        bytes,
+        optional_header(bytes)? + data_directories + index as u64 * 8,
+        8,
+    )?;
+
+    update_check_sum(bytes, header)
+}
+
+/// Recomputes the checksum if the image had one. Images with a zero checksum keep it zero.
+fn update_check_sum(bytes: &mut [u8], header: &ImageHeader) -> ReadImageResult<()> {
+    if header.check_sum == 0 {
+        return Ok(());
+    }
+
+    let check_sum = crate::pe::checksum::compute(&mut Cursor::new(&*bytes))?;
+    let at = optional_header(bytes)? + 64;
+    zero(bytes, at, 4)?;
+    bytes[at as usize..at as usize + 4].copy_from_slice(&check_sum.to_le_bytes());
+    Ok(())
+}
+
+fn optional_header(bytes: &[u8]) -> ReadImageResult<u64> {
+    let e_lfanew = bytes.get(0x3C..0x40).ok_or(ReadImageError::InvalidImage)?;
+    let e_lfanew = u32::from_le_bytes([e_lfanew[0], e_lfanew[1], e_lfanew[2], e_lfanew[3]]);
+    Ok(e_lfanew as u64 + 4 + 20)
+}
+
+/// Zeroes the file data of a debug entry. A pointer of zero means the entry has no file data,
+/// and data running past the end of the file is cut off there.
+fn zero_data(bytes: &mut [u8], pointer_to_raw_data: u32, size_of_data: u32) {
+    if pointer_to_raw_data == 0 {
+        return;
+    }
+    let start = (pointer_to_raw_data as usize).min(bytes.len());
+    let end = start.saturating_add(size_of_data as usize).min(bytes.len());
+    bytes[start..end].fill(0);
+}
+
+fn zero(bytes: &mut [u8], offset: u64, len: u32) -> ReadImageResult<()> {
+    let end = offset + len as u64;
+    bytes
+        .get_mut(offset as usize..end as usize)
+        .ok_or(ReadImageError::InvalidImage)?
+        .fill(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::debug::{DebugDirectory, IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB};
+    use crate::pe::resource::ResourceDirectory;
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() {
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+
+        super::strip_debug(&mut bytes).expect("success");
+        super::strip_resources(&mut bytes).expect("success");
+
+        let mut data = Cursor::new(&bytes);
+        let header = ImageHeader::read(&mut data).expect("success");
+        assert_eq!(header.debug.rva, 0);
+        assert!(DebugDirectory::read(&header, &mut data)
+            .expect("success")
+            .entries
+            .is_empty());
+        assert!(ResourceDirectory::read(&header, &mut data)
+            .expect("success")
+            .entries
+            .is_empty());
+        assert!(!bytes.windows(14).any(|w| w == b"HelloWorld.pdb"));
+
+        // Image-level reading is unaffected
+        crate::image::Image::read(&mut data).expect("success");
+
+        // With the debug directory gone, there's no embedded PDB left to strip
+        let stripped = bytes.clone();
+        super::strip_embedded_pdb(&mut bytes).expect("success");
+        assert_eq!(bytes, stripped);
+
+        // Turn the data-less reproducible entry into an embedded PDB in the slack space at the
+        // end of .text, and give the PDB checksum entry a size but no file data
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let dir = 0x788;
+        let entry = |i: usize| dir + i * 28;
+        bytes[entry(2) + 12..entry(2) + 28].copy_from_slice(
+            &[IMAGE_DEBUG_TYPE_EMBEDDED_PORTABLE_PDB, 0x20, 0x26C0, 0x8C0]
+                .map(u32::to_le_bytes)
+                .concat(),
+        );
+        bytes[0x8C0..0x8E0].copy_from_slice(b"MPDB\x40\0\0\0compressed portable pdb.");
+        bytes[entry(1) + 24..entry(1) + 28].fill(0);
+        let original = bytes.clone();
+
+        super::strip_embedded_pdb(&mut bytes).expect("success");
+        assert!(bytes[0x8C0..0x8E0].iter().all(|&b| b == 0));
+        assert!(bytes[entry(2) + 16..entry(2) + 28].iter().all(|&b| b == 0));
+        assert_eq!(bytes[..entry(2) + 16], original[..entry(2) + 16]);
+        assert_eq!(bytes[entry(2) + 28..0x8C0], original[entry(2) + 28..0x8C0]);
+        assert_eq!(bytes[0x8E0..], original[0x8E0..]);
+
+        let header = ImageHeader::read(&mut Cursor::new(&bytes)).expect("success");
+        let debug = DebugDirectory::read(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(debug.entries.len(), 3);
+        assert!(debug.code_view().is_some());
+
+        // Entries without file data don't touch the headers
+        let mut bytes = original;
+        super::strip_debug(&mut bytes).expect("success");
+        assert_eq!(&bytes[..2], b"MZ");
+        assert!(bytes[0x8C0..0x8E0].iter().all(|&b| b == 0));
+    }
+}
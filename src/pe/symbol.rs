@@ -0,0 +1,179 @@
+use crate::error::Context;
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::ImageHeader;
+use crate::read;
+use std::io::{Read, Seek};
+
+/// The COFF symbol table and the long names from its string table. Images rarely have one, since
+/// COFF debug information is deprecated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u32,
+    /// One-based index into the section table, or 0, -1 (absolute) and -2 (debug).
+    pub section_number: i16,
+    pub symbol_type: u16,
+    pub storage_class: u8,
+    /// Auxiliary records that follow the symbol, whose layout depends on the storage class.
+    pub aux: Vec<[u8; 18]>,
+}
+
+impl SymbolTable {
+    /// Reads the symbol table the COFF header points to, or returns `None` if there isn't one.
+    ///
+    /// This isn't part of [`ImageHeader::read`], since the table usually sits at the end of the
+    /// file, and a damaged one shouldn't stop the rest of the image from being read.
+    pub fn read(
+        header: &ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        match header.pointer_to_symbol_table {
+            0 => Ok(None),
+            ptr => Self::read_at(ptr, header.number_of_symbols, data)
+                .map(Some)
+                .context(ptr as u64, || "COFF symbol table".into()),
+        }
+    }
+
+    /// Reads `number_of_symbols` records at `pointer_to_symbol_table`, followed by the string
+    /// table. Auxiliary records count towards `number_of_symbols`.
+    fn read_at(
+        pointer_to_symbol_table: u32,
+        number_of_symbols: u32,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Self> {
+        read!(data for: goto pointer_to_symbol_table,);
+
+        let mut raw = Vec::with_capacity(number_of_symbols.min(0x1000) as usize);
+        let mut remaining = number_of_symbols;
+
+        while remaining > 0 {
+            let mut name = [0; 8];
+            data.read_exact(&mut name)?;
+
+            read!(data for:
+                value: u32,
+                section_number: i16,
+                symbol_type: u16,
+                storage_class: u8,
+                number_of_aux_symbols: u8,
+            );
+
+            remaining -= 1;
+
+            if number_of_aux_symbols as u32 > remaining {
+                return Err(ReadImageError::InvalidImage);
+            }
+
+            let mut aux = Vec::with_capacity(number_of_aux_symbols as usize);
+            for _ in 0..number_of_aux_symbols {
+                let mut record = [0; 18];
+                data.read_exact(&mut record)?;
+                aux.push(record);
+            }
+            remaining -= number_of_aux_symbols as u32;
+
+            raw.push((name, value, section_number, symbol_type, storage_class, aux));
+        }
+
+        // The string table directly follows the symbols, and its size includes the size field
+        let size = read! { data u32 }.saturating_sub(4);
+        let mut strings = vec![];
+        (&mut data).take(size as u64).read_to_end(&mut strings)?;
+        if strings.len() != size as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let mut symbols = Vec::with_capacity(raw.len());
+
+        for (name, value, section_number, symbol_type, storage_class, aux) in raw {
+            let name = if name[..4] == [0; 4] {
+                let offset = u32::from_le_bytes([name[4], name[5], name[6], name[7]]) as usize;
+                let bytes = offset
+                    .checked_sub(4)
+                    .and_then(|start| strings.get(start..))
+                    .ok_or(ReadImageError::InvalidImage)?;
+                let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                std::str::from_utf8(&bytes[..len])?.to_owned()
+            } else {
+                let len = name.iter().position(|&b| b == 0).unwrap_or(8);
+                std::str::from_utf8(&name[..len])?.to_owned()
+            };
+
+            symbols.push(Symbol {
+                name,
+                value,
+                section_number,
+                symbol_type,
+                storage_class,
+                aux,
+            });
+        }
+
+        Ok(Self { symbols })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let header = ImageHeader::read(&mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(
+            super::SymbolTable::read(&header, &mut Cursor::new(&bytes)).expect("success"),
+            None
+        );
+
+        // Append a short name with one aux record, then a long name from the string table
+        let pointer = bytes.len() as u32;
+        bytes.extend(b".text\0\0\0");
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(1i16.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        bytes.extend([3, 1]);
+        bytes.extend([0xAA; 18]);
+        bytes.extend([0; 4]);
+        bytes.extend(4u32.to_le_bytes());
+        bytes.extend(0x10u32.to_le_bytes());
+        bytes.extend(1i16.to_le_bytes());
+        bytes.extend(0x20u16.to_le_bytes());
+        bytes.extend([2, 0]);
+        bytes.extend(24u32.to_le_bytes());
+        bytes.extend(b"a_long_symbol_name\0\0");
+
+        // COFF PointerToSymbolTable and NumberOfSymbols
+        bytes[0x8C..0x90].copy_from_slice(&pointer.to_le_bytes());
+        bytes[0x90..0x94].copy_from_slice(&3u32.to_le_bytes());
+
+        let header = ImageHeader::read(&mut Cursor::new(&bytes)).expect("success");
+        let table = super::SymbolTable::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("symbol table");
+
+        assert_eq!(table.symbols.len(), 2);
+        assert_eq!(table.symbols[0].name, ".text");
+        assert_eq!(table.symbols[0].aux, vec![[0xAA; 18]]);
+        assert_eq!(table.symbols[1].name, "a_long_symbol_name");
+        assert_eq!(table.symbols[1].value, 0x10);
+
+        // A damaged table fails on its own, but the rest of the image still reads, even forward
+        bytes[0x8C..0x90].copy_from_slice(&0xFFFF_FF00u32.to_le_bytes());
+        let header = ImageHeader::read(&mut Cursor::new(&bytes)).expect("success");
+        let e = super::SymbolTable::read(&header, &mut Cursor::new(&bytes)).expect_err("failure");
+        assert_eq!(e.path(), ["COFF symbol table"]);
+        crate::image::Image::read_forward(bytes.as_slice()).expect("success");
+
+        Ok(())
+    }
+}
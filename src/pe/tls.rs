@@ -0,0 +1,165 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::{offset_from, ImageHeader};
+use crate::read;
+use std::io::{Read, Seek};
+
+/// The TLS directory. Unlike most PE structures, its addresses are VAs, not RVAs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsDirectory {
+    pub start_address_of_raw_data: u64,
+    pub end_address_of_raw_data: u64,
+    pub address_of_index: u64,
+    pub address_of_callbacks: u64,
+    pub size_of_zero_fill: u32,
+    pub characteristics: u32,
+    /// The VAs of the TLS callbacks, which run before the entry point.
+    pub callbacks: Vec<u64>,
+}
+
+impl TlsDirectory {
+    /// Reads the TLS directory. Returns `None` if the image has none.
+    pub fn read(
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let dir = header.tls;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(None);
+        }
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        let (
+            start_address_of_raw_data,
+            end_address_of_raw_data,
+            address_of_index,
+            address_of_callbacks,
+        ) = if header.pe64 {
+            read! { data for: a: u64, b: u64, c: u64, d: u64, };
+            (a, b, c, d)
+        } else {
+            read! { data for: a: u32, b: u32, c: u32, d: u32, };
+            (a as u64, b as u64, c as u64, d as u64)
+        };
+
+        read!(data for:
+            size_of_zero_fill: u32,
+            characteristics: u32,
+        );
+
+        let mut callbacks = vec![];
+
+        if address_of_callbacks != 0 {
+            let rva = address_of_callbacks
+                .checked_sub(header.image_base)
+                .and_then(|rva| u32::try_from(rva).ok())
+                .ok_or(ReadImageError::InvalidImage)?;
+            let offset = offset_from(&header.sections, rva).ok_or(ReadImageError::InvalidImage)?;
+
+            read!(data for: goto offset,);
+
+            loop {
+                let callback = match header.pe64 {
+                    true => read! { data u64 },
+                    false => (read! { data u32 }) as u64,
+                };
+                if callback == 0 {
+                    break;
+                }
+                callbacks.push(callback);
+            }
+        }
+
+        Ok(Some(Self {
+            start_address_of_raw_data,
+            end_address_of_raw_data,
+            address_of_index,
+            address_of_callbacks,
+            size_of_zero_fill,
+            characteristics,
+            callbacks,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::{DataDirectory, ImageHeader};
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let mut header = ImageHeader::read(&mut data).expect("success");
+
+        assert_eq!(
+            super::TlsDirectory::read(&header, &mut data).expect("success"),
+            None
+        );
+
+        // Fake directories in the slack space at the end of .text, at RVA 0x26C0, with the
+        // callback array at RVA 0x2700
+        header.sections[0].virtual_size = 0x800;
+        header.tls = DataDirectory {
+            rva: 0x26C0,
+            size: 24,
+        };
+
+        let mut bytes = include_bytes!("../../HelloWorld.dll").to_vec();
+        let tls: Vec<u8> = [0x402000u32, 0x402010, 0x402020, 0x402700, 0x10, 0x300000]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        bytes[0x8C0..0x8D8].copy_from_slice(&tls);
+        bytes[0x900..0x90C]
+            .copy_from_slice(&[0x00, 0x10, 0x40, 0, 0x10, 0x10, 0x40, 0, 0, 0, 0, 0]);
+
+        let tls = super::TlsDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("tls directory");
+        assert_eq!(tls.address_of_index, 0x402020);
+        assert_eq!(tls.size_of_zero_fill, 0x10);
+        assert_eq!(tls.characteristics, 0x300000);
+        assert_eq!(tls.callbacks, [0x401000, 0x401010]);
+
+        // PE32+ widens the four addresses
+        header.pe64 = true;
+        header.image_base = 0x1_8000_0000;
+        header.tls.size = 40;
+
+        let mut tls: Vec<u8> = [
+            0x1_8000_2000u64,
+            0x1_8000_2010,
+            0x1_8000_2020,
+            0x1_8000_2700,
+        ]
+        .into_iter()
+        .flat_map(u64::to_le_bytes)
+        .collect();
+        tls.extend(0x20u32.to_le_bytes());
+        tls.extend(0x500000u32.to_le_bytes());
+        bytes[0x8C0..0x8E8].copy_from_slice(&tls);
+        let callbacks: Vec<u8> = [0x1_8000_1000u64, 0]
+            .into_iter()
+            .flat_map(u64::to_le_bytes)
+            .collect();
+        bytes[0x900..0x910].copy_from_slice(&callbacks);
+
+        let tls = super::TlsDirectory::read(&header, &mut Cursor::new(&bytes))
+            .expect("success")
+            .expect("tls directory");
+        assert_eq!(tls.start_address_of_raw_data, 0x1_8000_2000);
+        assert_eq!(tls.address_of_callbacks, 0x1_8000_2700);
+        assert_eq!(tls.size_of_zero_fill, 0x20);
+        assert_eq!(tls.characteristics, 0x500000);
+        assert_eq!(tls.callbacks, [0x1_8000_1000]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::pe::resource::{ResourceDirectory, RT_VERSION};
+use crate::pe::ImageHeader;
+use std::io::{Read, Seek};
+
+/// The decoded contents of an RT_VERSION resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub fixed: Option<FixedFileInfo>,
+    pub string_tables: Vec<StringTable>,
+    /// Language and code page pairs listed in the `Translation` var.
+    pub translations: Vec<(u16, u16)>,
+}
+
+/// The language-independent VS_FIXEDFILEINFO part of the version resource.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FixedFileInfo {
+    pub file_version: [u16; 4],
+    pub product_version: [u16; 4],
+    pub file_flags_mask: u32,
+    pub file_flags: u32,
+    pub file_os: u32,
+    pub file_type: u32,
+    pub file_subtype: u32,
+    pub file_date: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringTable {
+    /// The language and code page as eight hex digits, like `040904b0`.
+    pub key: String,
+    pub strings: Vec<(String, String)>,
+}
+
+impl VersionInfo {
+    /// Reads the first RT_VERSION resource. Returns `None` if the image has none.
+    pub fn read(
+        header: &ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let rsrc = ResourceDirectory::read(header, data)?;
+        let leaf = match rsrc
+            .subdirectory(RT_VERSION)
+            .and_then(|d| d.leaves().next())
+        {
+            Some(leaf) => *leaf,
+            None => return Ok(None),
+        };
+
+        Self::parse(&leaf.read_bytes(header, data)?).map(Some)
+    }
+
+    /// Decodes a raw VS_VERSIONINFO structure.
+    pub fn parse(buf: &[u8]) -> ReadImageResult<Self> {
+        let root = Block::parse(buf)?;
+        if root.key != "VS_VERSION_INFO" {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let fixed = match root.value.len() {
+            0 => None,
+            52.. => Some(FixedFileInfo::parse(root.value)?),
+            _ => return Err(ReadImageError::InvalidImage),
+        };
+
+        let mut info = Self {
+            fixed,
+            string_tables: vec![],
+            translations: vec![],
+        };
+
+        for child in root.children() {
+            let child = child?;
+            match child.key.as_str() {
+                "StringFileInfo" => {
+                    for table in child.children() {
+                        let table = table?;
+                        let mut strings = vec![];
+                        for string in table.children() {
+                            let string = string?;
+                            strings.push((string.key.clone(), string.text()?));
+                        }
+                        info.string_tables.push(StringTable {
+                            key: table.key,
+                            strings,
+                        });
+                    }
+                }
+                "VarFileInfo" => {
+                    for var in child.children() {
+                        let var = var?;
+                        if var.key == "Translation" {
+                            info.translations.extend(
+                                var.value
+                                    .chunks_exact(4)
+                                    .map(|c| (u16le(c, 0), u16le(c, 2))),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Looks up a string value like `CompanyName`, `FileVersion` or `ProductVersion` in the first
+    /// string table that defines it.
+    pub fn string(&self, key: &str) -> Option<&str> {
+        self.string_tables
+            .iter()
+            .flat_map(|t| t.strings.iter())
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl FixedFileInfo {
+    fn parse(buf: &[u8]) -> ReadImageResult<Self> {
+        if u32le(buf, 0) != 0xFEEF04BD {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let version = |at| {
+            let ms = u32le(buf, at);
+            let ls = u32le(buf, at + 4);
+            [(ms >> 16) as u16, ms as u16, (ls >> 16) as u16, ls as u16]
+        };
+
+        Ok(Self {
+            file_version: version(8),
+            product_version: version(16),
+            file_flags_mask: u32le(buf, 24),
+            file_flags: u32le(buf, 28),
+            file_os: u32le(buf, 32),
+            file_type: u32le(buf, 36),
+            file_subtype: u32le(buf, 40),
+            file_date: (u32le(buf, 44) as u64) << 32 | u32le(buf, 48) as u64,
+        })
+    }
+}
+
+/// One node of the version resource's generic `wLength, wValueLength, wType, szKey, Value,
+/// Children` layout.
+struct Block<'a> {
+    key: String,
+    is_text: bool,
+    value: &'a [u8],
+    children: &'a [u8],
+}
+
+impl<'a> Block<'a> {
+    fn parse(buf: &'a [u8]) -> ReadImageResult<Self> {
+        if buf.len() < 6 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let length = (u16le(buf, 0) as usize).min(buf.len());
+        let value_length = u16le(buf, 2) as usize;
+        let is_text = u16le(buf, 4) == 1;
+        let buf = &buf[..length];
+
+        let mut pos = 6;
+        let mut units = vec![];
+        loop {
+            if pos + 2 > buf.len() {
+                return Err(ReadImageError::InvalidImage);
+            }
+            let unit = u16le(buf, pos);
+            pos += 2;
+            if unit == 0 {
+                break;
+            }
+            units.push(unit);
+        }
+        let key = String::from_utf16(&units).map_err(|_| ReadImageError::InvalidImage)?;
+
+        pos = align4(pos).min(buf.len());
+        let value_bytes = if is_text {
+            value_length * 2
+        } else {
+            value_length
+        };
+        let value_end = (pos + value_bytes).min(buf.len());
+        let value = &buf[pos..value_end];
+        let children = &buf[align4(value_end).min(buf.len())..];
+
+        Ok(Self {
+            key,
+            is_text,
+            value,
+            children,
+        })
+    }
+
+    fn children(&self) -> impl Iterator<Item = ReadImageResult<Block<'a>>> {
+        let mut rest = self.children;
+
+        std::iter::from_fn(move || {
+            if rest.len() < 6 {
+                return None;
+            }
+            let length = u16le(rest, 0) as usize;
+            if length == 0 {
+                return None;
+            }
+            let block = Block::parse(rest);
+            rest = &rest[align4(length).min(rest.len())..];
+            Some(block)
+        })
+    }
+
+    fn text(&self) -> ReadImageResult<String> {
+        if !self.is_text {
+            return Err(ReadImageError::InvalidImage);
+        }
+        let units: Vec<u16> = self
+            .value
+            .chunks_exact(2)
+            .map(|c| u16le(c, 0))
+            .take_while(|&u| u != 0)
+            .collect();
+        String::from_utf16(&units).map_err(|_| ReadImageError::InvalidImage)
+    }
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+fn u16le(buf: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([buf[at], buf[at + 1]])
+}
+
+fn u32le(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::pe::ImageHeader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut data = include_bytes!("../../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let header = ImageHeader::read(&mut data).expect("success");
+        let info = super::VersionInfo::read(&header, &mut data)
+            .expect("success")
+            .expect("version resource");
+
+        assert_eq!(info.fixed.expect("fixed info").file_version, [1, 0, 0, 0]);
+        assert_eq!(info.string("FileVersion"), Some("1.0.0.0"));
+        assert_eq!(info.string("ProductName"), Some("HelloWorld"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,632 @@
+use crate::cli::CliHeader;
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::flags::flags;
+use crate::pe::export::{Export, ExportDirectory, ExportTarget};
+use crate::pe::{offset_from, offset_range_from, read_c_str, DataDirectory, ImageHeader, Machine};
+use crate::read;
+use std::io::{Read, Seek};
+
+/// `RTR` in little-endian.
+pub const READYTORUN_SIGNATURE: u32 = 0x0052_5452;
+
+pub const SECTION_COMPILER_IDENTIFIER: u32 = 100;
+pub const SECTION_IMPORT_SECTIONS: u32 = 101;
+pub const SECTION_RUNTIME_FUNCTIONS: u32 = 102;
+pub const SECTION_METHOD_DEF_ENTRY_POINTS: u32 = 103;
+pub const SECTION_EXCEPTION_INFO: u32 = 104;
+pub const SECTION_DEBUG_INFO: u32 = 105;
+pub const SECTION_DELAY_LOAD_METHOD_CALL_THUNKS: u32 = 106;
+pub const SECTION_AVAILABLE_TYPES: u32 = 108;
+pub const SECTION_INSTANCE_METHOD_ENTRY_POINTS: u32 = 109;
+pub const SECTION_INLINING_INFO: u32 = 110;
+pub const SECTION_PROFILE_DATA_INFO: u32 = 111;
+pub const SECTION_MANIFEST_METADATA: u32 = 112;
+pub const SECTION_ATTRIBUTE_PRESENCE: u32 = 113;
+pub const SECTION_INLINING_INFO2: u32 = 114;
+pub const SECTION_COMPONENT_ASSEMBLIES: u32 = 115;
+pub const SECTION_OWNER_COMPOSITE_EXECUTABLE: u32 = 116;
+
+flags! {
+    /// The `READYTORUN_FLAG_*` values of the ReadyToRun header.
+    pub struct ReadyToRunFlags: u32 {
+        const PLATFORM_NEUTRAL_SOURCE = 0x1;
+        const SKIP_TYPE_VALIDATION = 0x2;
+        const PARTIAL = 0x4;
+        const NONSHARED_PINVOKE_STUBS = 0x8;
+        const EMBEDDED_MSIL = 0x10;
+        /// This image is one component of a composite image.
+        const COMPONENT = 0x20;
+        const MULTIMODULE_VERSION_BUBBLE = 0x40;
+        const UNRELATED_R2R_CODE = 0x80;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadyToRunHeader {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub flags: ReadyToRunFlags,
+    pub sections: Vec<ReadyToRunSection>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReadyToRunSection {
+    pub section_type: u32,
+    pub section: DataDirectory,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImportSection {
+    pub section: DataDirectory,
+    pub flags: u16,
+    pub import_type: u8,
+    pub entry_size: u8,
+    pub signatures: u32,
+    pub auxiliary_data: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RuntimeFunction {
+    pub begin_address: u32,
+    /// Only recorded on x64; other platforms derive it from the unwind data.
+    pub end_address: Option<u32>,
+    pub unwind_data: u32,
+}
+
+impl ReadyToRunHeader {
+    /// Reads the ReadyToRun header pointed to by the CLI header's managed native header.
+    ///
+    /// Returns `None` if there's no managed native header or it isn't a ReadyToRun header, which
+    /// is the case for plain IL images and legacy NGen images.
+    pub fn read(
+        header: &ImageHeader,
+        cli: &CliHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let dir = cli.managed_native_header;
+        if dir.rva == 0 || dir.size == 0 {
+            return Ok(None);
+        }
+
+        Self::read_at(header, dir.rva, data)
+    }
+
+    /// Reads a ReadyToRun header at an RVA, such as the `RTR_HEADER` export of a composite image.
+    pub fn read_at(
+        header: &ImageHeader,
+        rva: u32,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let offset = offset_from(&header.sections, rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for:
+            goto offset,
+            signature: u32,
+        );
+
+        if signature != READYTORUN_SIGNATURE {
+            return Ok(None);
+        }
+
+        read!(data for:
+            major_version: u16,
+            minor_version: u16,
+            flags: ReadyToRunFlags,
+            number_of_sections: u32,
+        );
+
+        let mut sections = Vec::with_capacity(number_of_sections.min(0x100) as usize);
+        for _ in 0..number_of_sections {
+            read!(data for:
+                section_type: u32,
+                section: DataDirectory,
+            );
+            sections.push(ReadyToRunSection {
+                section_type,
+                section,
+            });
+        }
+
+        Ok(Some(Self {
+            major_version,
+            minor_version,
+            flags,
+            sections,
+        }))
+    }
+
+    pub fn section(&self, section_type: u32) -> Option<DataDirectory> {
+        self.sections
+            .iter()
+            .find(|s| s.section_type == section_type)
+            .map(|s| s.section)
+    }
+
+    /// Reads the name and version of the compiler that produced the image, like `Crossgen2 6.0.0`.
+    pub fn compiler_identifier(
+        &self,
+        header: &ImageHeader,
+        data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<String>> {
+        let dir = match self.section(SECTION_COMPILER_IDENTIFIER) {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+        read_c_str(offset, data).map(Some)
+    }
+
+    pub fn import_sections(
+        &self,
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<ImportSection>> {
+        let dir = match self.section(SECTION_IMPORT_SECTIONS) {
+            Some(dir) => dir,
+            None => return Ok(vec![]),
+        };
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        let mut sections = Vec::with_capacity((dir.size / 20).min(0x100) as usize);
+        for _ in 0..dir.size / 20 {
+            read!(data for:
+                section: DataDirectory,
+                flags: u16,
+                import_type: u8,
+                entry_size: u8,
+                signatures: u32,
+                auxiliary_data: u32,
+            );
+            sections.push(ImportSection {
+                section,
+                flags,
+                import_type,
+                entry_size,
+                signatures,
+                auxiliary_data,
+            });
+        }
+
+        Ok(sections)
+    }
+
+    /// Reads the file name of the composite image that holds this component's native code.
+    pub fn owner_composite_executable(
+        &self,
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<String>> {
+        let dir = match self.section(SECTION_OWNER_COMPOSITE_EXECUTABLE) {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        // Read through `take` so that a bogus size can't allocate more than the file holds
+        let mut buf = vec![];
+        data.take(dir.size as u64).read_to_end(&mut buf)?;
+        if buf.len() != dir.size as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        Ok(Some(std::str::from_utf8(&buf[..len])?.to_owned()))
+    }
+
+    /// Reads the RUNTIME_FUNCTION table describing every compiled method body and funclet.
+    pub fn runtime_functions(
+        &self,
+        header: &ImageHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Vec<RuntimeFunction>> {
+        let dir = match self.section(SECTION_RUNTIME_FUNCTIONS) {
+            Some(dir) => dir,
+            None => return Ok(vec![]),
+        };
+
+        let has_end = target_machine(header.machine) == Machine::Amd64;
+        let entry_size = if has_end { 12 } else { 8 };
+
+        let offset = offset_from(&header.sections, dir.rva).ok_or(ReadImageError::InvalidImage)?;
+
+        read!(data for: goto offset,);
+
+        let mut functions = Vec::with_capacity((dir.size / entry_size).min(0x100) as usize);
+        for _ in 0..dir.size / entry_size {
+            let begin_address = read! { data u32 };
+            let end_address = match has_end {
+                true => Some(read! { data u32 }),
+                false => None,
+            };
+            let unwind_data = read! { data u32 };
+            functions.push(RuntimeFunction {
+                begin_address,
+                end_address,
+                unwind_data,
+            });
+        }
+
+        Ok(functions)
+    }
+}
+
+/// The version information of a legacy NGen image, from the CORCOMPILE_VERSION_INFO that its
+/// CORCOMPILE_HEADER points to.
+///
+/// Only the fields whose layout stayed the same across runtime versions are read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NGenHeader {
+    pub os_platform_id: u16,
+    pub os_major_version: u16,
+    /// The version of the runtime the image was compiled for, like `4.0.30319.0`.
+    pub runtime_version: [u16; 4],
+    pub machine: Machine,
+    /// The `CORCOMPILE_CODEGEN_*` flags, like debuggable or profiling code.
+    pub codegen_flags: u16,
+    /// The `CORCOMPILE_CONFIG_*` flags.
+    pub config_flags: u16,
+    /// The `CORCOMPILE_BUILD_*` flags.
+    pub build_flags: u16,
+}
+
+impl NGenHeader {
+    /// Reads the NGen version information pointed to by the CLI header's managed native header.
+    ///
+    /// Returns `None` unless the header looks like a CORCOMPILE_HEADER: it must be large enough
+    /// for its leading VersionInfo and StrongNameSignature directories, and the version
+    /// information must fit in the image and name the same machine as the COFF header.
+    pub fn read(
+        header: &ImageHeader,
+        cli: &CliHeader,
+        mut data: &mut (impl Read + Seek),
+    ) -> ReadImageResult<Option<Self>> {
+        let dir = cli.managed_native_header;
+        let offset = match offset_range_from(&header.sections, dir.rva, 16) {
+            Some(offset) if dir.size >= 16 => offset,
+            _ => return Ok(None),
+        };
+
+        read!(data for:
+            goto offset,
+            version_info: DataDirectory,
+        );
+
+        let offset = match offset_range_from(&header.sections, version_info.rva, 20) {
+            Some(offset) if version_info.size >= 20 => offset,
+            _ => return Ok(None),
+        };
+
+        read!(data for:
+            goto offset,
+            os_platform_id: u16,
+            os_major_version: u16,
+            major: u16,
+            minor: u16,
+            build: u16,
+            revision: u16,
+            machine: u16,
+            codegen_flags: u16,
+            config_flags: u16,
+            build_flags: u16,
+        );
+
+        if machine != header.machine.raw() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            os_platform_id,
+            os_major_version,
+            runtime_version: [major, minor, build, revision],
+            machine: Machine::from(machine),
+            codegen_flags,
+            config_flags,
+            build_flags,
+        }))
+    }
+}
+
+/// How the code in an image is compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageKind {
+    /// A managed image with only IL, compiled at run time.
+    Il,
+    /// A managed image with precompiled ReadyToRun code.
+    ReadyToRun(ReadyToRunHeader),
+    /// A component of a composite ReadyToRun image. Its native code lives in the owner, named by
+    /// the OwnerCompositeExecutable section.
+    CompositeComponent {
+        header: ReadyToRunHeader,
+        owner: Option<String>,
+    },
+    /// The native image containing the code for a set of composite components. It has no CLI
+    /// header; its ReadyToRun header is found through the `RTR_HEADER` export.
+    Composite(ReadyToRunHeader),
+    /// A legacy NGen image, whose managed native header is a CORCOMPILE_HEADER.
+    NGen(NGenHeader),
+    /// Not a managed image at all.
+    Native,
+}
+
+impl ImageKind {
+    pub fn detect(header: &ImageHeader, data: &mut (impl Read + Seek)) -> ReadImageResult<Self> {
+        if header.clr_runtime_header.rva == 0 {
+            let exports = ExportDirectory::read(header, data)?;
+            let rtr = exports
+                .iter()
+                .flat_map(|e| &e.exports)
+                .find_map(|e| match e {
+                    Export {
+                        name: Some(name),
+                        target: ExportTarget::Rva(rva),
+                        ..
+                    } if name == "RTR_HEADER" => Some(*rva),
+                    _ => None,
+                });
+
+            return match rtr {
+                Some(rva) => match ReadyToRunHeader::read_at(header, rva, data)? {
+                    Some(r2r) => Ok(Self::Composite(r2r)),
+                    None => Err(ReadImageError::InvalidImage),
+                },
+                None => Ok(Self::Native),
+            };
+        }
+
+        let cli = CliHeader::read(header, data)?;
+
+        match ReadyToRunHeader::read(header, &cli, data)? {
+            Some(r2r) if r2r.flags.contains(ReadyToRunFlags::COMPONENT) => {
+                let owner = r2r.owner_composite_executable(header, data)?;
+                Ok(Self::CompositeComponent { header: r2r, owner })
+            }
+            Some(r2r) => Ok(Self::ReadyToRun(r2r)),
+            None => match NGenHeader::read(header, &cli, data)? {
+                Some(ngen) => Ok(Self::NGen(ngen)),
+                None => Ok(Self::Il),
+            },
+        }
+    }
+}
+
+/// Undoes the OS-specific XOR that ReadyToRun images apply to the COFF machine on non-Windows
+/// targets, so a Linux x64 image reports `Amd64`.
+pub fn target_machine(machine: Machine) -> Machine {
+    // Apple, FreeBSD, Linux, NetBSD and SunOS
+    const OS_OVERRIDES: [u16; 5] = [0x4644, 0xADC4, 0x7B79, 0x1993, 0x1992];
+
+    if let Machine::Unknown(raw) = machine {
+        for os in OS_OVERRIDES {
+            if let native @ (Machine::I386
+            | Machine::Amd64
+            | Machine::Arm
+            | Machine::ArmNt
+            | Machine::Arm64) = Machine::from(raw ^ os)
+            {
+                return native;
+            }
+        }
+    }
+
+    machine
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::cli::CliHeader;
+    use crate::pe::{DataDirectory, ImageHeader, Machine};
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        let mut bytes = include_bytes!("../HelloWorld.dll").to_vec();
+
+        let mut data = Cursor::new(&bytes);
+        let header = ImageHeader::read(&mut data).expect("success");
+        let mut cli = CliHeader::read(&header, &mut data).expect("success");
+
+        assert_eq!(
+            super::ReadyToRunHeader::read(&header, &cli, &mut data).expect("success"),
+            None
+        );
+
+        // Fake a header in the slack space at the end of .text, at RVA 0x26C0, extending the
+        // section's virtual size to cover it
+        bytes[0x180..0x184].copy_from_slice(&0x800u32.to_le_bytes());
+        let mut r2r = vec![];
+        r2r.extend(super::READYTORUN_SIGNATURE.to_le_bytes());
+        r2r.extend(9u16.to_le_bytes());
+        r2r.extend(2u16.to_le_bytes());
+        r2r.extend(0x1u32.to_le_bytes());
+        r2r.extend(3u32.to_le_bytes());
+        for (section_type, rva, size) in [
+            (super::SECTION_COMPILER_IDENTIFIER, 0x2700u32, 16u32),
+            (super::SECTION_IMPORT_SECTIONS, 0x2710, 40),
+            (super::SECTION_RUNTIME_FUNCTIONS, 0x2740, 24),
+        ] {
+            r2r.extend(section_type.to_le_bytes());
+            r2r.extend(rva.to_le_bytes());
+            r2r.extend(size.to_le_bytes());
+        }
+        bytes[0x8C0..0x8C0 + r2r.len()].copy_from_slice(&r2r);
+        bytes[0x900..0x910].copy_from_slice(b"Crossgen2 test\0\0");
+
+        let mut imports = vec![];
+        for (rva, size, flags, import_type, entry_size, signatures) in [
+            (0x3000u32, 0x10u32, 0x1u16, 0u8, 8u8, 0x3100u32),
+            (0x3010, 0x20, 0x4, 2, 4, 0),
+        ] {
+            imports.extend(rva.to_le_bytes());
+            imports.extend(size.to_le_bytes());
+            imports.extend(flags.to_le_bytes());
+            imports.extend([import_type, entry_size]);
+            imports.extend(signatures.to_le_bytes());
+            imports.extend(0u32.to_le_bytes());
+        }
+        bytes[0x910..0x938].copy_from_slice(&imports);
+
+        let functions: Vec<u8> = [0x1000u32, 0x1010, 0x2000, 0x1020, 0x1030, 0x2010]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        bytes[0x940..0x958].copy_from_slice(&functions);
+
+        cli.managed_native_header = DataDirectory {
+            rva: 0x26C0,
+            size: 0x40,
+        };
+
+        let mut data = Cursor::new(&bytes);
+        let header = ImageHeader::read(&mut data).expect("success");
+        let mut r2r = super::ReadyToRunHeader::read(&header, &cli, &mut data)
+            .expect("success")
+            .expect("r2r header");
+
+        assert_eq!(r2r.major_version, 9);
+        assert!(r2r
+            .flags
+            .contains(super::ReadyToRunFlags::PLATFORM_NEUTRAL_SOURCE));
+        assert_eq!(
+            r2r.compiler_identifier(&header, &mut data)
+                .expect("success")
+                .as_deref(),
+            Some("Crossgen2 test")
+        );
+
+        let imports = r2r.import_sections(&header, &mut data).expect("success");
+        assert_eq!(
+            imports,
+            [
+                super::ImportSection {
+                    section: DataDirectory {
+                        rva: 0x3000,
+                        size: 0x10
+                    },
+                    flags: 0x1,
+                    import_type: 0,
+                    entry_size: 8,
+                    signatures: 0x3100,
+                    auxiliary_data: 0,
+                },
+                super::ImportSection {
+                    section: DataDirectory {
+                        rva: 0x3010,
+                        size: 0x20
+                    },
+                    flags: 0x4,
+                    import_type: 2,
+                    entry_size: 4,
+                    signatures: 0,
+                    auxiliary_data: 0,
+                },
+            ]
+        );
+
+        // Without an end address on x86, and with one on x64
+        let functions = r2r.runtime_functions(&header, &mut data).expect("success");
+        assert_eq!(functions.len(), 3);
+        assert_eq!(
+            functions[1],
+            super::RuntimeFunction {
+                begin_address: 0x2000,
+                end_address: None,
+                unwind_data: 0x1020,
+            }
+        );
+
+        let mut header64 = header.clone();
+        header64.machine = Machine::Amd64;
+        let functions = r2r
+            .runtime_functions(&header64, &mut data)
+            .expect("success");
+        assert_eq!(
+            functions,
+            [
+                super::RuntimeFunction {
+                    begin_address: 0x1000,
+                    end_address: Some(0x1010),
+                    unwind_data: 0x2000,
+                },
+                super::RuntimeFunction {
+                    begin_address: 0x1020,
+                    end_address: Some(0x1030),
+                    unwind_data: 0x2010,
+                },
+            ]
+        );
+
+        // Sizes larger than the file fail instead of allocating them
+        for section in &mut r2r.sections {
+            section.section.size = 0xFFFF_FFF0;
+        }
+        r2r.sections.push(super::ReadyToRunSection {
+            section_type: super::SECTION_OWNER_COMPOSITE_EXECUTABLE,
+            section: DataDirectory {
+                rva: 0x2700,
+                size: 0xFFFF_FFF0,
+            },
+        });
+        assert!(r2r.import_sections(&header, &mut data).is_err());
+        assert!(r2r.runtime_functions(&header, &mut data).is_err());
+        assert!(r2r.owner_composite_executable(&header, &mut data).is_err());
+
+        r2r.sections.last_mut().expect("owner section").section.size = 16;
+        assert_eq!(
+            r2r.owner_composite_executable(&header, &mut data)
+                .expect("success")
+                .as_deref(),
+            Some("Crossgen2 test")
+        );
+
+        // Point the CLI header's managed native header at the fake one, then at zeros
+        bytes[0x248..0x250].copy_from_slice(&[0xC0, 0x26, 0, 0, 0x40, 0, 0, 0]);
+        let kind = super::ImageKind::detect(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert!(matches!(kind, super::ImageKind::ReadyToRun(_)));
+
+        // Without the signature, the header is garbage as a CORCOMPILE_HEADER
+        bytes[0x8C0..0x8C4].fill(0);
+        let kind = super::ImageKind::detect(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(kind, super::ImageKind::Il);
+
+        // A CORCOMPILE_HEADER whose VersionInfo points at a CORCOMPILE_VERSION_INFO
+        bytes[0x8C0..0x8C8].copy_from_slice(&[0x00, 0x27, 0, 0, 0x40, 0, 0, 0]);
+        let version_info: Vec<u8> = [2u16, 5, 4, 0, 30319, 0, 0x14C, 0x1, 0, 0x2]
+            .into_iter()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        bytes[0x900..0x914].copy_from_slice(&version_info);
+        let kind = super::ImageKind::detect(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(
+            kind,
+            super::ImageKind::NGen(super::NGenHeader {
+                os_platform_id: 2,
+                os_major_version: 5,
+                runtime_version: [4, 0, 30319, 0],
+                machine: Machine::I386,
+                codegen_flags: 0x1,
+                config_flags: 0,
+                build_flags: 0x2,
+            })
+        );
+
+        // Version information for another machine isn't from an NGen image
+        bytes[0x90C..0x90E].copy_from_slice(&Machine::Amd64.raw().to_le_bytes());
+        let kind = super::ImageKind::detect(&header, &mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(kind, super::ImageKind::Il);
+
+        let plain = include_bytes!("../HelloWorld.dll");
+        let kind = super::ImageKind::detect(&header, &mut Cursor::new(plain)).expect("success");
+        assert_eq!(kind, super::ImageKind::Il);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+use crate::read;
+use std::io::{Cursor, Read};
+
+/// The magic number at the start of a `.resources` file.
+pub const RESOURCE_MANAGER_MAGIC: u32 = 0xBEEF_CACE;
+
+/// The contents of a `.resources` file, the binary format `ResourceManager` reads and the usual
+/// payload of embedded manifest resources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceSet {
+    pub reader_type: String,
+    pub set_type: String,
+    pub version: u32,
+    /// User type names referenced by serialized values.
+    pub types: Vec<String>,
+    pub entries: Vec<(String, ResourceValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceValue {
+    Null,
+    String(String),
+    Boolean(bool),
+    /// A UTF-16 code unit.
+    Char(u16),
+    Byte(u8),
+    SByte(i8),
+    Int16(i16),
+    UInt16(u16),
+    Int32(i32),
+    UInt32(u32),
+    Int64(i64),
+    UInt64(u64),
+    Single(f32),
+    Double(f64),
+    /// The raw bits of a `System.Decimal`.
+    Decimal([u8; 16]),
+    /// The raw bits of a `System.DateTime`, including its kind in the top two bits.
+    DateTime(i64),
+    /// A `System.TimeSpan` in 100-nanosecond ticks.
+    TimeSpan(i64),
+    ByteArray(Vec<u8>),
+    Stream(Vec<u8>),
+    /// A value of a user type, serialized with a formatter this crate doesn't decode.
+    Serialized {
+        type_name: String,
+    },
+}
+
+impl ResourceSet {
+    pub fn parse(buf: &[u8]) -> ReadImageResult<Self> {
+        let mut data = Cursor::new(buf);
+
+        read!(data for:
+            magic: u32,
+            header_version: u32,
+            header_len: u32,
+        );
+
+        if magic != RESOURCE_MANAGER_MAGIC {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let header_start = data.position();
+        let (reader_type, set_type) = match header_version {
+            1 => (read_string(&mut data)?, read_string(&mut data)?),
+            _ => (String::new(), String::new()),
+        };
+
+        read!(data for:
+            goto header_start + header_len as u64,
+            version: u32,
+            num_resources: u32,
+            num_types: u32,
+        );
+
+        if version != 1 && version != 2 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        let mut types = Vec::with_capacity(num_types.min(0x100) as usize);
+        for _ in 0..num_types {
+            types.push(read_string(&mut data)?);
+        }
+
+        // The name hash table is aligned to 8 bytes
+        let aligned = (data.position() + 7) & !7;
+        read!(data for: goto aligned,);
+
+        let count = num_resources as u64;
+        read!(data for:
+            skip count * 4, // name hashes
+            skip count * 4, // name positions, which just mirror the name section's order
+            data_section_offset: u32,
+        );
+
+        let mut names = Vec::with_capacity(num_resources.min(0x1000) as usize);
+        for _ in 0..num_resources {
+            let name = read_utf16_string(&mut data)?;
+            let offset = read! { data u32 };
+            names.push((name, offset));
+        }
+
+        let mut entries = Vec::with_capacity(names.len());
+        for (name, offset) in names {
+            read!(data for: goto data_section_offset as u64 + offset as u64,);
+
+            let value = read_value(&mut data, version, &types)?;
+            entries.push((name, value));
+        }
+
+        Ok(Self {
+            reader_type,
+            set_type,
+            version,
+            types,
+            entries,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResourceValue> {
+        self.entries.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+}
+
+fn read_value(
+    mut data: &mut Cursor<&[u8]>,
+    version: u32,
+    types: &[String],
+) -> ReadImageResult<ResourceValue> {
+    let code = read_7bit(data)?;
+
+    // Version 1 files only have indices into the type table, with -1 for null
+    if version == 1 {
+        if code == u32::MAX {
+            return Ok(ResourceValue::Null);
+        }
+        let type_name = types
+            .get(code as usize)
+            .ok_or(ReadImageError::InvalidImage)?;
+        return Ok(ResourceValue::Serialized {
+            type_name: type_name.clone(),
+        });
+    }
+
+    Ok(match code {
+        0 => ResourceValue::Null,
+        1 => ResourceValue::String(read_string(data)?),
+        2 => ResourceValue::Boolean(read! { data u8 } != 0),
+        3 => ResourceValue::Char(read! { data u16 }),
+        4 => ResourceValue::Byte(read! { data u8 }),
+        5 => ResourceValue::SByte(read! { data i8 }),
+        6 => ResourceValue::Int16(read! { data i16 }),
+        7 => ResourceValue::UInt16(read! { data u16 }),
+        8 => ResourceValue::Int32(read! { data i32 }),
+        9 => ResourceValue::UInt32(read! { data u32 }),
+        10 => ResourceValue::Int64(read! { data i64 }),
+        11 => ResourceValue::UInt64(read! { data u64 }),
+        12 => ResourceValue::Single(read! { data f32 }),
+        13 => ResourceValue::Double(read! { data f64 }),
+        14 => {
+            let mut bits = [0; 16];
+            data.read_exact(&mut bits)?;
+            ResourceValue::Decimal(bits)
+        }
+        15 => ResourceValue::DateTime(read! { data i64 }),
+        16 => ResourceValue::TimeSpan(read! { data i64 }),
+        0x20 | 0x21 => {
+            let len = read! { data u32 };
+            let bytes = read_bytes(data, len)?;
+            match code {
+                0x20 => ResourceValue::ByteArray(bytes),
+                _ => ResourceValue::Stream(bytes),
+            }
+        }
+        code if code >= 0x40 => {
+            let type_name = types
+                .get(code as usize - 0x40)
+                .ok_or(ReadImageError::InvalidImage)?;
+            ResourceValue::Serialized {
+                type_name: type_name.clone(),
+            }
+        }
+        _ => return Err(ReadImageError::InvalidImage),
+    })
+}
+
+/// Reads a `BinaryWriter`-style 7-bit encoded integer.
+fn read_7bit(mut data: &mut impl Read) -> ReadImageResult<u32> {
+    let mut value = 0u32;
+
+    for shift in (0..35).step_by(7) {
+        let b = read! { data u8 };
+        value |= ((b & 0x7F) as u32) << shift;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(ReadImageError::InvalidImage)
+}
+
+/// Reads `len` bytes, failing before allocating if there aren't that many left.
+fn read_bytes(data: &mut Cursor<&[u8]>, len: u32) -> ReadImageResult<Vec<u8>> {
+    let remaining = (data.get_ref().len() as u64).saturating_sub(data.position());
+    if len as u64 > remaining {
+        return Err(ReadImageError::InvalidImage);
+    }
+
+    let mut bytes = vec![0; len as usize];
+    data.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads a length-prefixed UTF-8 string, as written by `BinaryWriter.Write(string)`.
+fn read_string(data: &mut Cursor<&[u8]>) -> ReadImageResult<String> {
+    let len = read_7bit(data)?;
+    let bytes = read_bytes(data, len)?;
+    Ok(String::from_utf8(bytes).map_err(|e| e.utf8_error())?)
+}
+
+/// Reads a resource name, which is length-prefixed like any other string but encoded in UTF-16.
+fn read_utf16_string(data: &mut Cursor<&[u8]>) -> ReadImageResult<String> {
+    let len = read_7bit(data)?;
+    let bytes = read_bytes(data, len)?;
+
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16(&units).map_err(|_| ReadImageError::InvalidImage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResourceSet, ResourceValue};
+
+    fn string(out: &mut Vec<u8>, s: &[u8]) {
+        out.push(s.len() as u8);
+        out.extend(s);
+    }
+
+    /// Builds a resource set from UTF-16 names and encoded values.
+    fn build(version: u32, types: &[&[u8]], entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(super::RESOURCE_MANAGER_MAGIC.to_le_bytes());
+        buf.extend(1u32.to_le_bytes());
+        buf.extend(4u32.to_le_bytes());
+        string(&mut buf, b"R");
+        string(&mut buf, b"S");
+        buf.extend(version.to_le_bytes());
+        buf.extend((entries.len() as u32).to_le_bytes());
+        buf.extend((types.len() as u32).to_le_bytes());
+        for ty in types {
+            string(&mut buf, ty);
+        }
+        while buf.len() % 8 != 0 {
+            buf.push(b'P');
+        }
+        buf.extend(vec![0; entries.len() * 8]);
+
+        let data_section_at = buf.len();
+        buf.extend(0u32.to_le_bytes());
+        let mut values: Vec<u8> = vec![];
+        for (name, value) in entries {
+            string(&mut buf, name);
+            buf.extend((values.len() as u32).to_le_bytes());
+            values.extend(*value);
+        }
+
+        let data_section = buf.len() as u32;
+        buf[data_section_at..data_section_at + 4].copy_from_slice(&data_section.to_le_bytes());
+        buf.extend(values);
+        buf
+    }
+
+    #[test]
+    fn it_works() {
+        let buf = build(
+            2,
+            &[],
+            &[
+                (b"h\0i\0", &[1, 3, b'h', b'e', b'y']),
+                (b"n\0", &[8, 42, 0, 0, 0]),
+            ],
+        );
+        let set = ResourceSet::parse(&buf).expect("success");
+
+        assert_eq!(set.reader_type, "R");
+        assert_eq!(set.get("hi"), Some(&ResourceValue::String("hey".into())));
+        assert_eq!(set.get("n"), Some(&ResourceValue::Int32(42)));
+
+        // Version 1 uses type indices, with -1 for null
+        let buf = build(
+            1,
+            &[b"System.String"],
+            &[(b"a\0", &[0]), (b"b\0", &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F])],
+        );
+        let set = ResourceSet::parse(&buf).expect("success");
+        assert_eq!(
+            set.get("a"),
+            Some(&ResourceValue::Serialized {
+                type_name: "System.String".into()
+            })
+        );
+        assert_eq!(set.get("b"), Some(&ResourceValue::Null));
+
+        // Lengths past the end of the data fail instead of allocating
+        for value in [
+            &[0x20, 0xFF, 0xFF, 0xFF, 0xFF][..],
+            &[1, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F],
+        ] {
+            let buf = build(2, &[], &[(b"x\0", value)]);
+            assert!(ResourceSet::parse(&buf).is_err());
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::constant::{element_type, ConstantValue};
+use crate::error::ReadImageError;
+use crate::error::ReadImageResult;
+
+/// The permission set blob of a DeclSecurity row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionSet {
+    /// The .NET 1.x form: an XML document, returned as text.
+    Xml(String),
+    /// The .NET 2.0 binary form, a list of security attributes with their named arguments.
+    Attributes(Vec<SecurityAttribute>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityAttribute {
+    /// The assembly-qualified attribute type name, which can be parsed as a
+    /// [`TypeName`](crate::type_name::TypeName).
+    pub type_name: String,
+    pub named_args: Vec<NamedArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArgument {
+    /// True for a field, false for a property.
+    pub is_field: bool,
+    pub name: String,
+    pub value: AttributeValue,
+}
+
+/// A value in custom attribute encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    /// Any of the primitive types. Strings use [`AttributeValue::String`] instead.
+    Primitive(ConstantValue),
+    String(Option<String>),
+    /// A `System.Type`, as a reflection type name.
+    Type(Option<String>),
+    /// An enum value. The underlying type isn't recorded in the blob and can't be resolved
+    /// without the referenced assembly, so it's assumed to be `int`, as it is for every
+    /// permission enum in the framework.
+    Enum {
+        type_name: String,
+        value: i32,
+    },
+    Array(Option<Vec<AttributeValue>>),
+}
+
+const SZARRAY: u8 = 0x1D;
+const TYPE: u8 = 0x50;
+const BOXED: u8 = 0x51;
+const FIELD: u8 = 0x53;
+const PROPERTY: u8 = 0x54;
+const ENUM: u8 = 0x55;
+
+/// The element type of a field or property, including the enum name for enums.
+enum ArgType {
+    Simple(u8),
+    Enum(String),
+    Array(Box<ArgType>),
+}
+
+impl PermissionSet {
+    pub fn parse(blob: &[u8]) -> ReadImageResult<Self> {
+        match blob.first() {
+            Some(b'.') => {}
+            Some(_) => return Self::parse_xml(blob),
+            None => return Err(ReadImageError::InvalidImage),
+        }
+
+        let mut blob = Blob(&blob[1..]);
+        let count = blob.compressed()?;
+
+        let mut attributes = Vec::with_capacity(count.min(0x100) as usize);
+        for _ in 0..count {
+            let type_name = blob.ser_string()?.ok_or(ReadImageError::InvalidImage)?;
+            let len = blob.compressed()?;
+            let mut args = Blob(blob.take(len as usize)?);
+
+            let arg_count = args.compressed()?;
+            let mut named_args = Vec::with_capacity(arg_count.min(0x100) as usize);
+            for _ in 0..arg_count {
+                let is_field = match args.u8()? {
+                    FIELD => true,
+                    PROPERTY => false,
+                    _ => return Err(ReadImageError::InvalidImage),
+                };
+                let ty = args.arg_type()?;
+                let name = args.ser_string()?.ok_or(ReadImageError::InvalidImage)?;
+                let value = args.value(&ty, 0)?;
+                named_args.push(NamedArgument {
+                    is_field,
+                    name,
+                    value,
+                });
+            }
+
+            attributes.push(SecurityAttribute {
+                type_name,
+                named_args,
+            });
+        }
+
+        Ok(Self::Attributes(attributes))
+    }
+
+    fn parse_xml(blob: &[u8]) -> ReadImageResult<Self> {
+        if blob.len() % 2 != 0 {
+            return Err(ReadImageError::InvalidImage);
+        }
+        let units: Vec<u16> = blob
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16(&units)
+            .map(Self::Xml)
+            .map_err(|_| ReadImageError::InvalidImage)
+    }
+}
+
+struct Blob<'a>(&'a [u8]);
+
+impl<'a> Blob<'a> {
+    fn take(&mut self, n: usize) -> ReadImageResult<&'a [u8]> {
+        if n > self.0.len() {
+            return Err(ReadImageError::InvalidImage);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> ReadImageResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// An ECMA-335 II.23.2 compressed unsigned integer.
+    fn compressed(&mut self) -> ReadImageResult<u32> {
+        let b = self.u8()? as u32;
+        Ok(match b {
+            _ if b & 0x80 == 0 => b,
+            _ if b & 0xC0 == 0x80 => (b & 0x3F) << 8 | self.u8()? as u32,
+            _ if b & 0xE0 == 0xC0 => {
+                let rest = self.take(3)?;
+                (b & 0x1F) << 24 | (rest[0] as u32) << 16 | (rest[1] as u32) << 8 | rest[2] as u32
+            }
+            _ => return Err(ReadImageError::InvalidImage),
+        })
+    }
+
+    /// A length-prefixed UTF-8 string, where a length byte of 0xFF means null.
+    fn ser_string(&mut self) -> ReadImageResult<Option<String>> {
+        if self.0.first() == Some(&0xFF) {
+            self.take(1)?;
+            return Ok(None);
+        }
+        let len = self.compressed()?;
+        let bytes = self.take(len as usize)?;
+        Ok(Some(std::str::from_utf8(bytes)?.to_owned()))
+    }
+
+    fn arg_type(&mut self) -> ReadImageResult<ArgType> {
+        Ok(match self.u8()? {
+            SZARRAY => match self.arg_type()? {
+                // Arrays of arrays can't be encoded, so this is also a recursion limit
+                ArgType::Array(_) => return Err(ReadImageError::InvalidImage),
+                element => ArgType::Array(Box::new(element)),
+            },
+            ENUM => ArgType::Enum(self.ser_string()?.ok_or(ReadImageError::InvalidImage)?),
+            ty => ArgType::Simple(ty),
+        })
+    }
+
+    /// Boxed values can hold arrays of boxed values, so `depth` bounds the nesting.
+    fn value(&mut self, ty: &ArgType, depth: u32) -> ReadImageResult<AttributeValue> {
+        if depth > 8 {
+            return Err(ReadImageError::InvalidImage);
+        }
+
+        Ok(match ty {
+            ArgType::Simple(element_type::STRING) => AttributeValue::String(self.ser_string()?),
+            ArgType::Simple(TYPE) => AttributeValue::Type(self.ser_string()?),
+            ArgType::Simple(BOXED) => match self.arg_type()? {
+                ArgType::Simple(BOXED) => return Err(ReadImageError::InvalidImage),
+                ty => self.value(&ty, depth + 1)?,
+            },
+            ArgType::Simple(ty) => {
+                let len = match *ty {
+                    element_type::BOOLEAN | element_type::I1 | element_type::U1 => 1,
+                    element_type::CHAR | element_type::I2 | element_type::U2 => 2,
+                    element_type::I4 | element_type::U4 | element_type::R4 => 4,
+                    element_type::I8 | element_type::U8 | element_type::R8 => 8,
+                    _ => return Err(ReadImageError::InvalidImage),
+                };
+                AttributeValue::Primitive(ConstantValue::decode(*ty, self.take(len)?)?)
+            }
+            ArgType::Enum(type_name) => {
+                let bytes = self.take(4)?;
+                AttributeValue::Enum {
+                    type_name: type_name.clone(),
+                    value: i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                }
+            }
+            ArgType::Array(element) => {
+                let len = self.take(4)?;
+                let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]);
+                if len == u32::MAX {
+                    return Ok(AttributeValue::Array(None));
+                }
+                let mut items = Vec::with_capacity(len.min(0x1000) as usize);
+                for _ in 0..len {
+                    items.push(self.value(element, depth + 1)?);
+                }
+                AttributeValue::Array(Some(items))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributeValue, PermissionSet};
+    use crate::constant::ConstantValue;
+
+    fn ser_string(out: &mut Vec<u8>, s: &str) {
+        out.push(s.len() as u8);
+        out.extend(s.as_bytes());
+    }
+
+    #[test]
+    fn it_works() {
+        // [SecurityPermission(SecurityAction.RequestMinimum, UnmanagedCode = true,
+        //     Flags = SecurityPermissionFlag.Execution)]
+        let mut args = vec![2];
+        args.extend([0x54, 0x02]);
+        ser_string(&mut args, "UnmanagedCode");
+        args.push(1);
+        args.extend([0x54, 0x55]);
+        ser_string(
+            &mut args,
+            "System.Security.Permissions.SecurityPermissionFlag",
+        );
+        ser_string(&mut args, "Flags");
+        args.extend(8i32.to_le_bytes());
+
+        let mut blob = vec![b'.', 1];
+        ser_string(
+            &mut blob,
+            "System.Security.Permissions.SecurityPermissionAttribute, mscorlib",
+        );
+        blob.push(args.len() as u8);
+        blob.extend(args);
+
+        let PermissionSet::Attributes(attributes) = PermissionSet::parse(&blob).expect("success")
+        else {
+            panic!("expected the binary form");
+        };
+        assert_eq!(attributes.len(), 1);
+
+        let args = &attributes[0].named_args;
+        assert_eq!(args[0].name, "UnmanagedCode");
+        assert!(!args[0].is_field);
+        assert_eq!(
+            args[0].value,
+            AttributeValue::Primitive(ConstantValue::Boolean(true))
+        );
+        assert!(matches!(
+            args[1].value,
+            AttributeValue::Enum { value: 8, .. }
+        ));
+
+        let xml: Vec<u8> = "<PermissionSet/>"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            PermissionSet::parse(&xml).expect("success"),
+            PermissionSet::Xml("<PermissionSet/>".into())
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::ReadImageResult;
+use crate::image::Image;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+/// Adapts a plain reader, like a pipe or a network stream, to the `Seek` bound of the image
+/// readers by only allowing seeks forward. Skipped bytes are read and discarded.
+///
+/// This works as long as the structures being read appear in file order, which is the case for
+/// the PE headers and CLI header of images produced by the usual compilers. Seeking backwards
+/// fails with `ErrorKind::Unsupported`.
+#[derive(Debug)]
+pub struct ForwardReader<R> {
+    inner: R,
+    pos: u64,
+}
+
+impl<R: Read> ForwardReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    /// The number of bytes consumed from the underlying reader so far.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ForwardReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read> Seek for ForwardReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+            SeekFrom::End(_) => None,
+        };
+
+        let target = match target {
+            Some(target) if target >= self.pos => target,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "forward-only streams can't seek backwards or from the end",
+                ))
+            }
+        };
+
+        let skipped = io::copy(
+            &mut (&mut self.inner).take(target - self.pos),
+            &mut io::sink(),
+        )?;
+        self.pos += skipped;
+
+        if self.pos != target {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(self.pos)
+    }
+}
+
+/// A parsed image and its bytes, shared behind `Arc`s so that each thread can take its own
+/// cursor instead of contending for one `&mut` reader. Cloning is cheap.
+#[derive(Debug, Clone)]
+pub struct SharedReader {
+    image: Arc<Image>,
+    bytes: Arc<[u8]>,
+}
+
+impl SharedReader {
+    pub fn new(bytes: impl Into<Arc<[u8]>>) -> ReadImageResult<Self> {
+        let bytes = bytes.into();
+        let image = Image::read(&mut Cursor::new(&*bytes))?;
+        Ok(Self {
+            image: Arc::new(image),
+            bytes,
+        })
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns a new cursor over the image, independent of every other cursor.
+    pub fn cursor(&self) -> Cursor<Arc<[u8]>> {
+        Cursor::new(self.bytes.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom};
+
+    use super::ForwardReader;
+
+    #[test]
+    fn it_works() -> std::io::Result<()> {
+        // &[u8] implements Read but not Seek
+        let data = include_bytes!("../HelloWorld.dll").as_ref();
+
+        let image = crate::image::Image::read_forward(data).expect("success");
+        assert_eq!(image.cli.entry_point_token, 0x06000001);
+
+        let mut reader = ForwardReader::new(data);
+        reader.seek(SeekFrom::Start(0x80))?;
+        assert!(reader.seek(SeekFrom::Start(0x40)).is_err());
+
+        let shared = super::SharedReader::new(include_bytes!("../HelloWorld.dll").to_vec())
+            .expect("success");
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    crate::pe::debug::DebugDirectory::read(
+                        &shared.image().header,
+                        &mut shared.cursor(),
+                    )
+                    .expect("success")
+                    .entries
+                    .len()
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            assert_eq!(thread.join().expect("thread"), 3);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Table identifiers, as used in the high byte of a metadata token.
+pub mod table {
+    pub const MODULE: u8 = 0x00;
+    pub const TYPE_REF: u8 = 0x01;
+    pub const TYPE_DEF: u8 = 0x02;
+    pub const FIELD_PTR: u8 = 0x03;
+    pub const FIELD: u8 = 0x04;
+    pub const METHOD_PTR: u8 = 0x05;
+    pub const METHOD_DEF: u8 = 0x06;
+    pub const PARAM_PTR: u8 = 0x07;
+    pub const PARAM: u8 = 0x08;
+    pub const INTERFACE_IMPL: u8 = 0x09;
+    pub const MEMBER_REF: u8 = 0x0A;
+    pub const CONSTANT: u8 = 0x0B;
+    pub const CUSTOM_ATTRIBUTE: u8 = 0x0C;
+    pub const FIELD_MARSHAL: u8 = 0x0D;
+    pub const DECL_SECURITY: u8 = 0x0E;
+    pub const CLASS_LAYOUT: u8 = 0x0F;
+    pub const FIELD_LAYOUT: u8 = 0x10;
+    pub const STAND_ALONE_SIG: u8 = 0x11;
+    pub const EVENT_MAP: u8 = 0x12;
+    pub const EVENT_PTR: u8 = 0x13;
+    pub const EVENT: u8 = 0x14;
+    pub const PROPERTY_MAP: u8 = 0x15;
+    pub const PROPERTY_PTR: u8 = 0x16;
+    pub const PROPERTY: u8 = 0x17;
+    pub const METHOD_SEMANTICS: u8 = 0x18;
+    pub const METHOD_IMPL: u8 = 0x19;
+    pub const MODULE_REF: u8 = 0x1A;
+    pub const TYPE_SPEC: u8 = 0x1B;
+    pub const IMPL_MAP: u8 = 0x1C;
+    pub const FIELD_RVA: u8 = 0x1D;
+    pub const ENC_LOG: u8 = 0x1E;
+    pub const ENC_MAP: u8 = 0x1F;
+    pub const ASSEMBLY: u8 = 0x20;
+    pub const ASSEMBLY_PROCESSOR: u8 = 0x21;
+    pub const ASSEMBLY_OS: u8 = 0x22;
+    pub const ASSEMBLY_REF: u8 = 0x23;
+    pub const ASSEMBLY_REF_PROCESSOR: u8 = 0x24;
+    pub const ASSEMBLY_REF_OS: u8 = 0x25;
+    pub const FILE: u8 = 0x26;
+    pub const EXPORTED_TYPE: u8 = 0x27;
+    pub const MANIFEST_RESOURCE: u8 = 0x28;
+    pub const NESTED_CLASS: u8 = 0x29;
+    pub const GENERIC_PARAM: u8 = 0x2A;
+    pub const METHOD_SPEC: u8 = 0x2B;
+    pub const GENERIC_PARAM_CONSTRAINT: u8 = 0x2C;
+    /// Not a table; `ldstr` tokens use this to index the #US heap.
+    pub const USER_STRING: u8 = 0x70;
+}
+
+/// A table identifier in the high byte and a row id in the low three bytes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MetadataToken(u32);
+
+impl MetadataToken {
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// Returns `None` if `rid` doesn't fit in 24 bits.
+    pub const fn from_parts(table: u8, rid: u32) -> Option<Self> {
+        if rid > 0x00FF_FFFF {
+            None
+        } else {
+            Some(Self((table as u32) << 24 | rid))
+        }
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub const fn table(self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    /// The one-based row id, or 0 for a null token.
+    pub const fn rid(self) -> u32 {
+        self.0 & 0x00FF_FFFF
+    }
+
+    pub const fn is_null(self) -> bool {
+        self.rid() == 0
+    }
+}
+
+impl From<u32> for MetadataToken {
+    fn from(raw: u32) -> Self {
+        Self(raw)
+    }
+}
+
+impl fmt::Display for MetadataToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:08X}", self.0)
+    }
+}
+
+impl fmt::Debug for MetadataToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MetadataToken(0x{:08X})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{table, MetadataToken};
+
+    #[test]
+    fn it_works() {
+        let token = MetadataToken::from_parts(table::METHOD_DEF, 1).expect("fits");
+
+        assert_eq!(token.raw(), 0x06000001);
+        assert_eq!(token.table(), table::METHOD_DEF);
+        assert_eq!(token.rid(), 1);
+        assert_eq!(token.to_string(), "0x06000001");
+        assert_eq!(
+            MetadataToken::from_parts(table::TYPE_DEF, 0x0100_0000),
+            None
+        );
+        assert!(MetadataToken::from_raw(0x02000000).is_null());
+    }
+}
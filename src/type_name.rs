@@ -0,0 +1,342 @@
+use crate::identity::{AssemblyIdentity, ParseIdentityError};
+use std::fmt;
+use std::str::FromStr;
+
+/// A reflection-style type name, like ``System.Collections.Generic.List`1[[System.Int32,
+/// mscorlib]], mscorlib, Version=4.0.0.0``, as found in `System.Type` custom attribute arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeName {
+    pub namespace: String,
+    pub name: String,
+    /// Names of nested types, outermost first, as separated by `+`.
+    pub nested: Vec<String>,
+    pub generic_args: Vec<TypeName>,
+    pub modifiers: Vec<TypeModifier>,
+    pub assembly: Option<AssemblyIdentity>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TypeModifier {
+    /// `*`
+    Pointer,
+    /// `&`
+    ByRef,
+    /// `[]`, a single-dimensional zero-based array.
+    SzArray,
+    /// `[*]` or `[,]`, a general array of the given rank.
+    Array(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTypeNameError {
+    /// The name is empty, a bracket is unbalanced or there's trailing input.
+    Malformed,
+    Assembly(ParseIdentityError),
+}
+
+impl From<ParseIdentityError> for ParseTypeNameError {
+    fn from(e: ParseIdentityError) -> Self {
+        Self::Assembly(e)
+    }
+}
+
+impl TypeName {
+    /// The namespace-qualified name of the type, including nested types but no generic arguments.
+    pub fn full_name(&self) -> String {
+        let mut full = String::new();
+        if !self.namespace.is_empty() {
+            full.push_str(&self.namespace);
+            full.push('.');
+        }
+        full.push_str(&self.name);
+        for nested in &self.nested {
+            full.push('+');
+            full.push_str(nested);
+        }
+        full
+    }
+}
+
+impl FromStr for TypeName {
+    type Err = ParseTypeNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { s, pos: 0 };
+        let name = parser.type_name(false)?;
+
+        parser.skip_whitespace();
+        if parser.pos != s.len() {
+            return Err(ParseTypeNameError::Malformed);
+        }
+
+        Ok(name)
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.s[self.pos..].chars().nth(1)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseTypeNameError> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(ParseTypeNameError::Malformed),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// A type and, optionally, its assembly. Inside brackets, the assembly name ends at the
+    /// closing bracket instead of the end of the input.
+    fn type_name(&mut self, bracketed: bool) -> Result<TypeName, ParseTypeNameError> {
+        let mut ty = self.type_without_assembly()?;
+
+        self.skip_whitespace();
+        if self.eat(',') {
+            let end = match bracketed {
+                true => self.closing_bracket()?,
+                false => self.s.len(),
+            };
+            ty.assembly = Some(self.s[self.pos..end].trim().parse()?);
+            self.pos = end;
+        }
+
+        Ok(ty)
+    }
+
+    fn type_without_assembly(&mut self) -> Result<TypeName, ParseTypeNameError> {
+        self.skip_whitespace();
+
+        let full = self.name()?;
+        let (namespace, name) = match full.rfind('.') {
+            Some(dot) => (full[..dot].to_owned(), full[dot + 1..].to_owned()),
+            None => (String::new(), full),
+        };
+
+        let mut nested = vec![];
+        while self.eat('+') {
+            nested.push(self.name()?);
+        }
+
+        let mut generic_args = vec![];
+        if self.peek() == Some('[') && !matches!(self.peek_second(), Some(']' | ',' | '*')) {
+            self.expect('[')?;
+            loop {
+                self.skip_whitespace();
+                if self.eat('[') {
+                    generic_args.push(self.type_name(true)?);
+                    self.expect(']')?;
+                } else {
+                    generic_args.push(self.type_without_assembly()?);
+                }
+                self.skip_whitespace();
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            self.expect(']')?;
+        }
+
+        let mut modifiers = vec![];
+        loop {
+            if self.eat('*') {
+                modifiers.push(TypeModifier::Pointer);
+            } else if self.eat('&') {
+                modifiers.push(TypeModifier::ByRef);
+            } else if self.eat('[') {
+                let mut rank = 1;
+                let mut star = false;
+                loop {
+                    match self.peek() {
+                        Some(',') => rank += 1,
+                        Some('*') => star = true,
+                        Some(']') => break,
+                        _ => return Err(ParseTypeNameError::Malformed),
+                    }
+                    self.pos += 1;
+                }
+                self.expect(']')?;
+                modifiers.push(match (rank, star) {
+                    (1, false) => TypeModifier::SzArray,
+                    (rank, _) => TypeModifier::Array(rank),
+                });
+            } else {
+                break;
+            }
+        }
+
+        Ok(TypeName {
+            namespace,
+            name,
+            nested,
+            generic_args,
+            modifiers,
+            assembly: None,
+        })
+    }
+
+    /// An identifier up to the next unescaped special character, unescaped.
+    fn name(&mut self) -> Result<String, ParseTypeNameError> {
+        let mut name = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => {
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or(ParseTypeNameError::Malformed)?;
+                    self.pos += escaped.len_utf8();
+                    name.push(escaped);
+                }
+                '[' | ']' | ',' | '+' | '*' | '&' => break,
+                c => {
+                    self.pos += c.len_utf8();
+                    name.push(c);
+                }
+            }
+        }
+
+        let name = name.trim_end().to_owned();
+        if name.is_empty() {
+            return Err(ParseTypeNameError::Malformed);
+        }
+        Ok(name)
+    }
+
+    /// Finds the unescaped `]` that ends a bracketed assembly name.
+    fn closing_bracket(&self) -> Result<usize, ParseTypeNameError> {
+        let mut chars = self.s[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                ']' => return Ok(self.pos + i),
+                _ => {}
+            }
+        }
+        Err(ParseTypeNameError::Malformed)
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        if matches!(c, '[' | ']' | ',' | '+' | '*' | '&' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.namespace.is_empty() {
+            write_escaped(f, &self.namespace)?;
+            f.write_str(".")?;
+        }
+        write_escaped(f, &self.name)?;
+        for nested in &self.nested {
+            f.write_str("+")?;
+            write_escaped(f, nested)?;
+        }
+
+        if !self.generic_args.is_empty() {
+            f.write_str("[")?;
+            for (i, arg) in self.generic_args.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                match arg.assembly {
+                    Some(_) => write!(f, "[{arg}]")?,
+                    None => write!(f, "{arg}")?,
+                }
+            }
+            f.write_str("]")?;
+        }
+
+        for modifier in &self.modifiers {
+            match modifier {
+                TypeModifier::Pointer => f.write_str("*")?,
+                TypeModifier::ByRef => f.write_str("&")?,
+                TypeModifier::SzArray => f.write_str("[]")?,
+                TypeModifier::Array(1) => f.write_str("[*]")?,
+                TypeModifier::Array(rank) => {
+                    f.write_str("[")?;
+                    for _ in 1..*rank {
+                        f.write_str(",")?;
+                    }
+                    f.write_str("]")?;
+                }
+            }
+        }
+
+        if let Some(assembly) = &self.assembly {
+            write!(f, ", {assembly}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TypeModifier, TypeName};
+
+    #[test]
+    fn it_works() {
+        let s = "System.Collections.Generic.Dictionary`2[[System.String, mscorlib, Version=4.0.0.0],Outer+Inner[]], mscorlib";
+        let ty: TypeName = s.parse().expect("success");
+
+        assert_eq!(ty.namespace, "System.Collections.Generic");
+        assert_eq!(ty.name, "Dictionary`2");
+        assert_eq!(ty.assembly.as_ref().expect("assembly").name, "mscorlib");
+        assert_eq!(ty.generic_args.len(), 2);
+        assert_eq!(ty.generic_args[0].full_name(), "System.String");
+        assert_eq!(
+            ty.generic_args[0]
+                .assembly
+                .as_ref()
+                .expect("assembly")
+                .version,
+            Some([4, 0, 0, 0])
+        );
+        assert_eq!(ty.generic_args[1].full_name(), "Outer+Inner");
+        assert_eq!(ty.generic_args[1].modifiers, vec![TypeModifier::SzArray]);
+        assert_eq!(ty.to_string(), s);
+
+        let ptr: TypeName = "Foo*[,]&".parse().expect("success");
+        assert_eq!(
+            ptr.modifiers,
+            vec![
+                TypeModifier::Pointer,
+                TypeModifier::Array(2),
+                TypeModifier::ByRef
+            ]
+        );
+
+        assert!("Foo[".parse::<TypeName>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::cli::CliHeader;
+use crate::pe::ImageHeader;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Something odd about an image that doesn't stop it from being read. Loaders accept these, but
+/// they're often a sign of a hand-crafted, obfuscated or damaged file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A field that should be zero isn't.
+    NonzeroReserved { field: &'static str, value: u64 },
+    /// A flags field has bits set that aren't defined.
+    UnknownFlags { field: &'static str, bits: u32 },
+    /// The COFF timestamp is later than the current time. Deterministic pseudo-timestamps are
+    /// not reported.
+    FutureTimestamp(u32),
+    /// The file alignment isn't a power of two between 512 and 64K, or the section alignment is
+    /// smaller than it.
+    UnusualAlignment {
+        section_alignment: u32,
+        file_alignment: u32,
+    },
+    /// A section's raw data doesn't start at a multiple of the file alignment.
+    MisalignedSection { index: usize },
+}
+
+pub(crate) fn check(header: &ImageHeader, cli: &CliHeader) -> Vec<Warning> {
+    let mut warnings = vec![];
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    if !header.has_pseudo_timestamp() && header.time_date_stamp as u64 > now {
+        warnings.push(Warning::FutureTimestamp(header.time_date_stamp));
+    }
+
+    let file_alignment = header.file_alignment;
+    let section_alignment = header.section_alignment;
+    let file_alignment_ok =
+        file_alignment.is_power_of_two() && (512..=0x10000).contains(&file_alignment);
+    if !file_alignment_ok || section_alignment < file_alignment {
+        warnings.push(Warning::UnusualAlignment {
+            section_alignment,
+            file_alignment,
+        });
+    }
+
+    if file_alignment_ok {
+        for (index, section) in header.sections.iter().enumerate() {
+            if section.pointer_to_raw_data % file_alignment != 0 {
+                warnings.push(Warning::MisalignedSection { index });
+            }
+        }
+    }
+
+    let unknown = [
+        (
+            "dll_characteristics",
+            header.dll_characteristics.unknown_bits() as u32,
+        ),
+        ("cli.flags", cli.flags.unknown_bits()),
+    ];
+    for (field, bits) in unknown {
+        if bits != 0 {
+            warnings.push(Warning::UnknownFlags { field, bits });
+        }
+    }
+
+    // The PE format reserves these
+    let reserved = [
+        ("win32_version_value", header.win32_version_value as u64),
+        ("loader_flags", header.loader_flags as u64),
+    ];
+    for (field, value) in reserved {
+        if value != 0 {
+            warnings.push(Warning::NonzeroReserved { field, value });
+        }
+    }
+
+    // ECMA-335 II.25.3.3 requires these to be zero
+    let reserved = [
+        ("cli.code_manager_table", cli.code_manager_table),
+        (
+            "cli.export_address_table_jumps",
+            cli.export_address_table_jumps,
+        ),
+    ];
+    for (field, dir) in reserved {
+        if dir.rva != 0 || dir.size != 0 {
+            let value = (dir.size as u64) << 32 | dir.rva as u64;
+            warnings.push(Warning::NonzeroReserved { field, value });
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::image::Image;
+
+    #[test]
+    fn it_works() {
+        let mut data = include_bytes!("../HelloWorld.dll").as_ref();
+        let mut data = Cursor::new(&mut data);
+
+        let mut image = Image::read(&mut data).expect("success");
+        assert!(image.warnings.is_empty());
+
+        image.header.file_alignment = 3;
+        image.cli.code_manager_table.rva = 0x10;
+        image.header.win32_version_value = 1;
+        let warnings = super::check(&image.header, &image.cli);
+        assert!(warnings.contains(&super::Warning::UnusualAlignment {
+            section_alignment: 0x2000,
+            file_alignment: 3,
+        }));
+        assert!(warnings.contains(&super::Warning::NonzeroReserved {
+            field: "cli.code_manager_table",
+            value: 0x10,
+        }));
+        assert!(warnings.contains(&super::Warning::NonzeroReserved {
+            field: "win32_version_value",
+            value: 1,
+        }));
+
+        // Read from the file: LoaderFlags follows the stack and heap sizes
+        let mut bytes = include_bytes!("../HelloWorld.dll").to_vec();
+        bytes[0x80 + 24 + 88..][..4].copy_from_slice(&0x1234u32.to_le_bytes());
+        let image = Image::read(&mut Cursor::new(&bytes)).expect("success");
+        assert_eq!(image.header.loader_flags, 0x1234);
+        assert_eq!(
+            image.warnings,
+            [super::Warning::NonzeroReserved {
+                field: "loader_flags",
+                value: 0x1234,
+            }]
+        );
+    }
+}